@@ -4,13 +4,23 @@ use gmeta::{In, InOut, Metadata as GMetadata, Out};
 use gstd::{
     collections::{BTreeMap, BTreeSet},
     prelude::*,
-    ActorId,
+    ActorId, MessageId,
 };
 
 pub type AttributeId = u32;
 pub type Price = u128;
 pub type TamagotchiId = ActorId;
 pub type TransactionId = u64;
+pub type BlockNumber = u32;
+pub type CouponCode = String;
+pub type TradeId = u64;
+pub type RecipeId = u32;
+
+pub mod v1;
+
+/// Version of the `AttributeStore` encoding used by `ExportState` and `ImportState`,
+/// states of the older versions are converted on import.
+pub const STATE_VERSION: u16 = 2;
 
 pub struct ProgramMetadata;
 
@@ -32,7 +42,66 @@ pub struct AttributeStore {
     pub attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
     pub owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
     pub transaction_id: TransactionId,
-    pub transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    pub transactions: BTreeMap<TamagotchiId, Transaction>,
+    pub tx_timeout: BlockNumber,
+    pub refund_policy: RefundPolicy,
+    pub purchases: BTreeMap<(TamagotchiId, AttributeId), Purchase>,
+    pub accepted_tokens: BTreeSet<ActorId>,
+    pub token_prices: BTreeMap<(AttributeId, ActorId), Price>,
+    pub revenue: BTreeMap<(AttributeId, ActorId), Price>,
+    pub total_revenue: BTreeMap<ActorId, Price>,
+    pub treasury: BTreeMap<ActorId, Price>,
+    pub revenue_splits: BTreeMap<AttributeId, Vec<(ActorId, u8)>>,
+    pub payouts: BTreeMap<(ActorId, ActorId), Price>,
+    pub coupons: BTreeMap<CouponCode, Coupon>,
+    pub availability: BTreeMap<AttributeId, Availability>,
+    pub sales: BTreeMap<AttributeId, Vec<Sale>>,
+    pub bonding_curves: BTreeMap<AttributeId, BondingCurve>,
+    pub trade_id: TradeId,
+    pub trades: BTreeMap<TradeId, Trade>,
+    pub effects: BTreeMap<AttributeId, Effects>,
+    pub consumables: BTreeMap<AttributeId, Consumable>,
+    pub inventory: BTreeMap<(TamagotchiId, AttributeId), u32>,
+    pub mystery_boxes: BTreeMap<AttributeId, Vec<(AttributeId, u32)>>,
+    pub draws: BTreeMap<TransactionId, Draw>,
+    pub recipes: BTreeMap<RecipeId, Recipe>,
+    pub crafts: BTreeMap<TamagotchiId, PendingCraft>,
+    pub presales: BTreeMap<AttributeId, Presale>,
+    pub whitelists: BTreeMap<AttributeId, BTreeSet<TamagotchiId>>,
+    pub receipts: BTreeMap<TamagotchiId, Vec<Receipt>>,
+    pub sale_count: u64,
+    pub loyalty_policy: LoyaltyPolicy,
+    pub loyalty: BTreeMap<TamagotchiId, LoyaltyAccount>,
+    pub points_prices: BTreeMap<AttributeId, u128>,
+    pub referral_percent: u8,
+    pub customers: BTreeSet<TamagotchiId>,
+    pub referred_by: BTreeMap<TamagotchiId, TamagotchiId>,
+    pub referrals: BTreeMap<TamagotchiId, u32>,
+    pub referral_rewards: BTreeMap<(TamagotchiId, ActorId), Price>,
+    pub awaiting: BTreeMap<MessageId, (TamagotchiId, TransactionId)>,
+    pub reconciliations: BTreeSet<TamagotchiId>,
+    pub locked: BTreeSet<TamagotchiId>,
+    pub frozen: bool,
+    pub native_prices: BTreeMap<AttributeId, u128>,
+    pub native_revenue: u128,
+    pub native_treasury: u128,
+    pub roles: BTreeMap<ActorId, BTreeSet<Role>>,
+    pub paused: bool,
+    pub transfers: BTreeMap<TransactionId, OutgoingTransfer>,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Role {
+    /// Has every other role and manages roles. The admin is always an owner.
+    Owner,
+    /// Adds attributes to the catalog.
+    CatalogManager,
+    /// Manages token contracts, refunds, revenue splits and withdrawals.
+    Treasurer,
+    /// Cleans up and retries stuck transactions and pauses the store.
+    Operator,
 }
 
 #[derive(Encode, Decode, Clone, TypeInfo, Debug)]
@@ -44,6 +113,280 @@ pub struct AttrMetadata {
     pub media: String,
 }
 
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Transaction {
+    pub id: TransactionId,
+    pub attribute_id: AttributeId,
+    pub created_at: BlockNumber,
+    /// FT contract the transaction started with, retries are sent to it as well.
+    pub token: ActorId,
+    /// Price quoted when the transaction was created, the buyer pays exactly this amount.
+    pub price: Price,
+    pub coupon: Option<CouponCode>,
+    /// Tamagotchi that gets the attribute, differs from the buyer for gifts.
+    pub recipient: TamagotchiId,
+    /// Loyalty points taken off `price`, reserved until the transaction is settled.
+    pub points: u128,
+    pub referrer: Option<TamagotchiId>,
+}
+
+#[derive(Default, Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct RefundPolicy {
+    /// Share of the paid price returned to the tamagotchi, in percent.
+    pub percent: u8,
+    /// Number of blocks after the purchase during which the attribute can be sold back.
+    pub window: BlockNumber,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Purchase {
+    pub block: BlockNumber,
+    pub price: Price,
+    pub currency: Currency,
+    /// Tamagotchi that paid for it, differs from the owner for gifts.
+    pub buyer: TamagotchiId,
+    /// Loyalty points the buyer earned with it, taken back if it's sold back.
+    pub points: u128,
+    /// Paid to the buyer's referrer, taken back from the referrer's payouts if it's sold back.
+    pub referral_reward: Price,
+}
+
+#[derive(Default, Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct LoyaltyPolicy {
+    /// Points awarded for a paid purchase, in percent of its price.
+    pub percent: u8,
+    /// Blocks after the last purchase when the points expire, zero if they never do.
+    pub expiry: BlockNumber,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct LoyaltyAccount {
+    pub points: u128,
+    pub expires_at: BlockNumber,
+}
+
+/// A part of the encoded `AttributeStore`, `checksum` is of the whole encoding.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct StateChunk {
+    pub version: u16,
+    pub index: u32,
+    pub total: u32,
+    pub checksum: u64,
+    pub data: Vec<u8>,
+}
+
+/// A purchase attempt kept in the buyer's history, failed ones included.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Receipt {
+    pub transaction_id: TransactionId,
+    pub buyer: TamagotchiId,
+    pub attribute_id: AttributeId,
+    pub price: Price,
+    pub currency: Currency,
+    pub block: BlockNumber,
+    pub success: bool,
+}
+
+// FNV-1a, computed the same way by the exporting and importing stores
+pub fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Currency {
+    FungibleToken(ActorId),
+    Native,
+}
+
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Coupon {
+    pub discount: Discount,
+    pub max_uses: u32,
+    pub uses: u32,
+    /// Last block at which the coupon can be redeemed.
+    pub expires_at: BlockNumber,
+    /// The only attribute the coupon applies to, if any.
+    pub attribute_id: Option<AttributeId>,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Discount {
+    Percent(u8),
+    /// Amount subtracted from the price in whatever currency the attribute is paid with.
+    Fixed(Price),
+}
+
+#[derive(Default, Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Availability {
+    pub available_from: Option<BlockNumber>,
+    pub available_until: Option<BlockNumber>,
+}
+
+/// A discount applied automatically to every purchase made between `starts_at` and `ends_at`.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Sale {
+    pub starts_at: BlockNumber,
+    pub ends_at: BlockNumber,
+    pub discount: Discount,
+}
+
+/// Until `ends_at` the attribute can only be bought for tamagotchis on its whitelist,
+/// with the optional presale discount.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Presale {
+    pub ends_at: BlockNumber,
+    pub discount: Option<Discount>,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct BondingCurve {
+    pub growth: CurveGrowth,
+    /// Multiplier of the attribute prices in basis points.
+    /// Starts at 10 000 and grows with every sale.
+    pub multiplier: u128,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum CurveGrowth {
+    /// Every sale adds `percent` of the base price.
+    Linear { percent: u16 },
+    /// Every sale raises the current price by `percent`.
+    Exponential { percent: u16 },
+}
+
+/// An offer to swap attributes. The `give` attribute is held by the store until
+/// the trade is accepted or cancelled.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Trade {
+    pub offerer: TamagotchiId,
+    pub give: AttributeId,
+    pub want: AttributeId,
+    pub counterparty: TamagotchiId,
+}
+
+/// Gameplay bonuses an attribute gives to the tamagotchi that owns it.
+#[derive(Default, Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Effects {
+    pub power: u32,
+    pub energy: u32,
+    /// Slows down the decay of fed, entertained and rested, in percent.
+    pub decay_reduction: u8,
+}
+
+/// What a consumable item (food pack, toy, energy drink) restores when it's used.
+/// Consumables are counted in `inventory` instead of `owners`.
+#[derive(Default, Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Consumable {
+    pub fed: u64,
+    pub entertained: u64,
+    pub rested: u64,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Draw {
+    pub tamagotchi_id: TamagotchiId,
+    pub box_id: AttributeId,
+    pub attribute_id: AttributeId,
+}
+
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Recipe {
+    /// Owned attributes consumed by crafting.
+    pub ingredients: Vec<AttributeId>,
+    /// Paid in the main fungible token, may be zero.
+    pub fee: Price,
+    pub result: AttributeId,
+}
+
+/// A craft whose fee is being paid, the store holds the ingredients until it's settled.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct PendingCraft {
+    pub transaction_id: TransactionId,
+    pub recipe_id: RecipeId,
+    pub recipe: Recipe,
+    pub token: ActorId,
+    /// Purchases of the ingredients, restored with them if the fee isn't paid.
+    pub purchases: Vec<Option<Purchase>>,
+}
+
+/// A transfer out of the store that isn't settled yet.
+/// If its message runs out of gas, it's sent again with `ReconcileTransfer`.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct OutgoingTransfer {
+    pub transaction_id: TransactionId,
+    pub token: ActorId,
+    pub to: ActorId,
+    pub amount: Price,
+    pub reason: TransferReason,
+}
+
+/// What's given back if the transfer fails.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum TransferReason {
+    /// The attribute goes back to the tamagotchi with the points and the referral reward
+    /// taken away when it was sold back.
+    Refund {
+        attribute_id: AttributeId,
+        purchase: Purchase,
+        points: u128,
+        referral_reward: Price,
+    },
+    /// The amount goes back to the treasury.
+    Withdrawal,
+    /// The amount goes back to the payouts of the recipient.
+    Payout,
+}
+
+// New variants of `StoreAction` and `StoreEvent` are only appended at the end,
+// so other contracts can rely on the encoding of the existing ones.
 #[derive(Encode, Decode, TypeInfo, Debug)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
@@ -53,28 +396,413 @@ pub enum StoreAction {
         attr_metadata: AttrMetadata,
         price: Price,
     },
+    /// Pays with the main FT contract, or with native value if it's attached to the message.
     BuyAttribute {
         attribute_id: AttributeId,
     },
     GetAttributes {
         tamagotchi_id: TamagotchiId,
     },
+    /// Fails while purchases paid with the current contract are pending.
     SetFtContractId {
         ft_contract_id: ActorId,
     },
     RemoveTx {
         tamagotchi_id: TamagotchiId,
     },
+    SellBack {
+        attribute_id: AttributeId,
+    },
+    SetRefundPolicy {
+        percent: u8,
+        window: BlockNumber,
+    },
+    Withdraw {
+        token: ActorId,
+        to: ActorId,
+        amount: Price,
+    },
+    SetRevenueSplit {
+        attribute_id: AttributeId,
+        beneficiaries: Vec<(ActorId, u8)>,
+    },
+    ClaimRevenue {
+        token: ActorId,
+    },
+    GrantRole {
+        account: ActorId,
+        role: Role,
+    },
+    RevokeRole {
+        account: ActorId,
+        role: Role,
+    },
+    TransferAdmin {
+        new_admin: ActorId,
+    },
+    Pause,
+    Unpause,
+    RetryTx {
+        tamagotchi_id: TamagotchiId,
+    },
+    /// Sent by the store to itself `tx_timeout` blocks after a transaction is created.
+    ExpireTx {
+        tamagotchi_id: TamagotchiId,
+        transaction_id: TransactionId,
+    },
+    SetTxTimeout {
+        timeout: BlockNumber,
+    },
+    SetNativePrice {
+        attribute_id: AttributeId,
+        price: Option<u128>,
+    },
+    WithdrawNative {
+        to: ActorId,
+        amount: u128,
+    },
+    AcceptToken {
+        token: ActorId,
+    },
+    RemoveToken {
+        token: ActorId,
+    },
+    SetTokenPrice {
+        attribute_id: AttributeId,
+        token: ActorId,
+        price: Option<Price>,
+    },
+    CreateCoupon {
+        code: CouponCode,
+        discount: Discount,
+        max_uses: u32,
+        expires_at: BlockNumber,
+        attribute_id: Option<AttributeId>,
+    },
+    RemoveCoupon {
+        code: CouponCode,
+    },
+    SetAvailability {
+        attribute_id: AttributeId,
+        available_from: Option<BlockNumber>,
+        available_until: Option<BlockNumber>,
+    },
+    ScheduleSale {
+        attribute_id: AttributeId,
+        sale: Sale,
+    },
+    ClearSales {
+        attribute_id: AttributeId,
+    },
+    /// Sets the curve the attribute price follows, or fixes the price if `growth` is `None`.
+    SetBondingCurve {
+        attribute_id: AttributeId,
+        growth: Option<CurveGrowth>,
+    },
+    TransferAttribute {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
+    OfferTrade {
+        give: AttributeId,
+        want: AttributeId,
+        counterparty: TamagotchiId,
+    },
+    AcceptTrade {
+        trade_id: TradeId,
+    },
+    CancelTrade {
+        trade_id: TradeId,
+    },
+    SetEffects {
+        attribute_id: AttributeId,
+        effects: Effects,
+    },
+    /// Replies with the sum of the effects of all the attributes the tamagotchi owns.
+    GetEffects {
+        tamagotchi_id: TamagotchiId,
+    },
+    /// Makes the attribute a consumable, or a regular attribute if `consumable` is `None`.
+    SetConsumable {
+        attribute_id: AttributeId,
+        consumable: Option<Consumable>,
+    },
+    /// Burns one unit of the consumable owned by the sender
+    /// and replies with what it restores.
+    ConsumeItem {
+        item: AttributeId,
+    },
+    /// Turns the attribute into a mystery box that gives one attribute from `pool`,
+    /// drawn with the given weights. An empty pool makes it a regular attribute again.
+    SetMysteryBox {
+        box_id: AttributeId,
+        pool: Vec<(AttributeId, u32)>,
+    },
+    /// Same as `BuyAttribute` for a mystery box, replies with `MysteryBoxOpened`.
+    BuyMysteryBox {
+        box_id: AttributeId,
+    },
+    /// Adds or replaces the recipe, or removes it if `recipe` is `None`.
+    SetRecipe {
+        recipe_id: RecipeId,
+        recipe: Option<Recipe>,
+    },
+    /// Retries the craft with the same transaction if the previous one wasn't settled.
+    Craft {
+        recipe_id: RecipeId,
+    },
+    /// Starts the presale of the attribute, or ends it if `presale` is `None`.
+    SetPresale {
+        attribute_id: AttributeId,
+        presale: Option<Presale>,
+    },
+    AddToWhitelist {
+        attribute_id: AttributeId,
+        tamagotchis: Vec<TamagotchiId>,
+    },
+    RemoveFromWhitelist {
+        attribute_id: AttributeId,
+        tamagotchis: Vec<TamagotchiId>,
+    },
+    SetLoyaltyPolicy {
+        percent: u8,
+        expiry: BlockNumber,
+    },
+    /// Makes the attribute redeemable for loyalty points, or not if `points` is `None`.
+    SetPointsPrice {
+        attribute_id: AttributeId,
+        points: Option<u128>,
+    },
+    RedeemPoints {
+        attribute_id: AttributeId,
+    },
+    /// Sets the share of the first purchase price paid to the referrer, in percent.
+    SetReferralReward {
+        percent: u8,
+    },
+    /// Resends the transfer of a transaction whose purchase ran out of gas,
+    /// replies like `RetryTx`.
+    ReconcileTx {
+        tamagotchi_id: TamagotchiId,
+    },
+    /// Stops everything but reads, pending transactions, withdrawals, payouts and the state migration.
+    Freeze,
+    Unfreeze,
+    /// Replies with a chunk of the store state, the store must be frozen with no pending transactions
+    /// or transfers, and with the treasury and the payouts emptied.
+    ExportState {
+        index: u32,
+    },
+    /// Chunks are imported in order, the last one replaces the whole state of the frozen store.
+    /// Version 1 chunks hold the state of the first store program, see `v1::AttributeStore`.
+    ImportState {
+        chunk: StateChunk,
+    },
+    /// Same as `BuyAttribute`, but the attribute goes to `recipient`.
+    GiftAttribute {
+        attribute_id: AttributeId,
+        recipient: TamagotchiId,
+    },
+    /// Same as `BuyAttribute`, but pays with the `pay_with` token (the main FT contract if it's `None`).
+    /// Up to `points` loyalty points are taken off a price in the main FT token.
+    /// The `referrer` is rewarded if it's the first purchase of the tamagotchi.
+    BuyAttributeWith {
+        attribute_id: AttributeId,
+        pay_with: Option<ActorId>,
+        coupon: Option<CouponCode>,
+        points: u128,
+        referrer: Option<TamagotchiId>,
+    },
+    /// Resends a refund, withdrawal or payout whose message ran out of gas,
+    /// with the same transaction id so it isn't paid twice.
+    ReconcileTransfer {
+        transaction_id: TransactionId,
+    },
+    /// Sends the payout of `beneficiary` in `token` without waiting for it to claim it,
+    /// replies like `ClaimRevenue`. Empties the payouts before the state is exported.
+    PayOut {
+        beneficiary: ActorId,
+        token: ActorId,
+    },
 }
 
 #[derive(Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum StoreEvent {
-    AttributeCreated { attribute_id: AttributeId },
-    AttributeSold { success: bool },
-    Attributes { attributes: BTreeSet<AttributeId> },
-    CompletePrevTx { attribute_id: AttributeId },
-    FtContractIdSet { ft_contract_id: ActorId },
-    TxRemoved { tamagotchi_id: ActorId },
+    AttributeCreated {
+        attribute_id: AttributeId,
+    },
+    AttributeSold {
+        success: bool,
+    },
+    Attributes {
+        attributes: BTreeSet<AttributeId>,
+    },
+    CompletePrevTx {
+        attribute_id: AttributeId,
+    },
+    FtContractIdSet {
+        ft_contract_id: ActorId,
+    },
+    TxRemoved {
+        tamagotchi_id: ActorId,
+    },
+    AttributeSoldBack {
+        success: bool,
+    },
+    RefundPolicySet {
+        percent: u8,
+        window: BlockNumber,
+    },
+    Withdrawn {
+        amount: Price,
+        success: bool,
+    },
+    RevenueSplitSet {
+        attribute_id: AttributeId,
+    },
+    RevenueClaimed {
+        amount: Price,
+        success: bool,
+    },
+    RoleGranted {
+        account: ActorId,
+        role: Role,
+    },
+    RoleRevoked {
+        account: ActorId,
+        role: Role,
+    },
+    AdminTransferred {
+        new_admin: ActorId,
+    },
+    Paused,
+    Unpaused,
+    Error(StoreError),
+    TxTimeoutSet {
+        timeout: BlockNumber,
+    },
+    NativePriceSet {
+        attribute_id: AttributeId,
+    },
+    NativeWithdrawn {
+        amount: u128,
+    },
+    TokenAccepted {
+        token: ActorId,
+    },
+    TokenRemoved {
+        token: ActorId,
+    },
+    TokenPriceSet {
+        attribute_id: AttributeId,
+    },
+    CouponCreated {
+        code: CouponCode,
+    },
+    CouponRemoved {
+        code: CouponCode,
+    },
+    AvailabilitySet {
+        attribute_id: AttributeId,
+    },
+    SaleScheduled {
+        attribute_id: AttributeId,
+    },
+    SalesCleared {
+        attribute_id: AttributeId,
+    },
+    BondingCurveSet {
+        attribute_id: AttributeId,
+    },
+    /// Sent to the recipient of a gift.
+    GiftReceived {
+        attribute_id: AttributeId,
+        from: ActorId,
+    },
+    AttributeTransferred {
+        attribute_id: AttributeId,
+        to: TamagotchiId,
+    },
+    TradeOffered {
+        trade_id: TradeId,
+    },
+    TradeAccepted {
+        trade_id: TradeId,
+    },
+    TradeCancelled {
+        trade_id: TradeId,
+    },
+    EffectsSet {
+        attribute_id: AttributeId,
+    },
+    Effects {
+        effects: Effects,
+    },
+    ConsumableSet {
+        attribute_id: AttributeId,
+    },
+    ItemConsumed {
+        item: AttributeId,
+        consumable: Consumable,
+    },
+    MysteryBoxSet {
+        box_id: AttributeId,
+    },
+    MysteryBoxOpened {
+        attribute_id: AttributeId,
+    },
+    RecipeSet {
+        recipe_id: RecipeId,
+    },
+    Crafted {
+        recipe_id: RecipeId,
+        attribute_id: AttributeId,
+    },
+    CraftFailed {
+        recipe_id: RecipeId,
+    },
+    PresaleSet {
+        attribute_id: AttributeId,
+    },
+    WhitelistUpdated {
+        attribute_id: AttributeId,
+    },
+    LoyaltyPolicySet {
+        percent: u8,
+        expiry: BlockNumber,
+    },
+    PointsPriceSet {
+        attribute_id: AttributeId,
+    },
+    PointsRedeemed {
+        attribute_id: AttributeId,
+        points: u128,
+    },
+    ReferralRewardSet {
+        percent: u8,
+    },
+    Frozen,
+    Unfrozen,
+    StateExported {
+        chunk: StateChunk,
+    },
+    StateImported {
+        index: u32,
+        complete: bool,
+    },
+    TransferReconciled {
+        transaction_id: TransactionId,
+        success: bool,
+    },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum StoreError {
+    Paused,
+    WhitelistOnly,
+    Frozen,
 }
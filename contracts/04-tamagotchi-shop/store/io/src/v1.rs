@@ -0,0 +1,33 @@
+//! Layout of the `AttributeStore` of the first store program, as its `state()` encodes it.
+//! The encoding is split into `StateChunk`s of version 1 and imported into the new store.
+
+use super::*;
+
+pub const STATE_VERSION: u16 = 1;
+
+#[derive(Default, Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct AttributeStore {
+    pub admin: ActorId,
+    pub ft_contract_id: ActorId,
+    pub attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
+    pub owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
+    pub transaction_id: TransactionId,
+    pub transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+}
+
+// pending transactions are left behind, the FT contract only knows the old program,
+// so they have to be removed with `RemoveTx` before the state is read
+impl From<AttributeStore> for super::AttributeStore {
+    fn from(state: AttributeStore) -> Self {
+        Self {
+            admin: state.admin,
+            ft_contract_id: state.ft_contract_id,
+            attributes: state.attributes,
+            owners: state.owners,
+            transaction_id: state.transaction_id,
+            ..Default::default()
+        }
+    }
+}
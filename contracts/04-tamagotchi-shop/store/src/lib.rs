@@ -4,106 +4,1800 @@ use gstd::{
     collections::{BTreeMap, BTreeSet},
     exec, msg,
     prelude::*,
-    ActorId,
+    ActorId, MessageId,
 };
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
 use store_io::{
-    AttrMetadata, AttributeId, Price, StoreAction, StoreEvent, TamagotchiId, TransactionId,
+    AttrMetadata, AttributeId, Availability, BlockNumber, BondingCurve, Consumable, Coupon,
+    CouponCode, Currency, CurveGrowth, Discount, Draw, Effects, LoyaltyAccount, LoyaltyPolicy,
+    OutgoingTransfer, PendingCraft, Presale, Price, Purchase, Receipt, Recipe, RecipeId,
+    RefundPolicy, Role, Sale, StateChunk, StoreAction, StoreError, StoreEvent, TamagotchiId, Trade,
+    TradeId, Transaction, TransactionId, TransferReason, STATE_VERSION,
 };
 
+// ~12 hours with 3-second blocks
+const DEFAULT_TX_TIMEOUT: BlockNumber = 14_400;
+// `BondingCurve::multiplier` of the base price
+const BASE_MULTIPLIER: u128 = 10_000;
+// receipts kept per buyer, the oldest ones are dropped first
+const MAX_RECEIPTS: usize = 50;
+// reserved by messages awaiting the FT contract for `handle_signal` in case they run out of gas
+const SIGNAL_GAS: u64 = 1_000_000_000;
+// keeps the exported chunks well below the message size limit
+const STATE_CHUNK_SIZE: usize = 32 * 1024;
+
 static mut STORE: Option<AttributeStore> = None;
 
-#[derive(Default)]
-struct AttributeStore {
-    admin: ActorId,
-    ft_contract_id: ActorId,
-    attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
-    owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
-    transaction_id: TransactionId,
-    transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
-}
+#[derive(Default)]
+struct AttributeStore {
+    admin: ActorId,
+    ft_contract_id: ActorId,
+    attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
+    owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
+    transaction_id: TransactionId,
+    transactions: BTreeMap<TamagotchiId, Transaction>,
+    tx_timeout: BlockNumber,
+    refund_policy: RefundPolicy,
+    purchases: BTreeMap<(TamagotchiId, AttributeId), Purchase>,
+    // fungible tokens accepted besides `ft_contract_id` and attribute prices in them
+    accepted_tokens: BTreeSet<ActorId>,
+    token_prices: BTreeMap<(AttributeId, ActorId), Price>,
+    // revenue, treasury and payouts are kept per token contract
+    revenue: BTreeMap<(AttributeId, ActorId), Price>,
+    total_revenue: BTreeMap<ActorId, Price>,
+    treasury: BTreeMap<ActorId, Price>,
+    // shares of an attribute's price (in percent) credited to beneficiaries on every sale
+    revenue_splits: BTreeMap<AttributeId, Vec<(ActorId, u8)>>,
+    payouts: BTreeMap<(ActorId, ActorId), Price>,
+    coupons: BTreeMap<CouponCode, Coupon>,
+    availability: BTreeMap<AttributeId, Availability>,
+    sales: BTreeMap<AttributeId, Vec<Sale>>,
+    bonding_curves: BTreeMap<AttributeId, BondingCurve>,
+    trade_id: TradeId,
+    trades: BTreeMap<TradeId, Trade>,
+    effects: BTreeMap<AttributeId, Effects>,
+    consumables: BTreeMap<AttributeId, Consumable>,
+    // quantities of the consumables owned by each tamagotchi
+    inventory: BTreeMap<(TamagotchiId, AttributeId), u32>,
+    // weighted pools of attributes the mystery boxes are drawn from
+    mystery_boxes: BTreeMap<AttributeId, Vec<(AttributeId, u32)>>,
+    draws: BTreeMap<TransactionId, Draw>,
+    recipes: BTreeMap<RecipeId, Recipe>,
+    crafts: BTreeMap<TamagotchiId, PendingCraft>,
+    presales: BTreeMap<AttributeId, Presale>,
+    // tamagotchis that can get the attribute during its presale
+    whitelists: BTreeMap<AttributeId, BTreeSet<TamagotchiId>>,
+    receipts: BTreeMap<TamagotchiId, Vec<Receipt>>,
+    // successful purchases over the whole store lifetime
+    sale_count: u64,
+    loyalty_policy: LoyaltyPolicy,
+    loyalty: BTreeMap<TamagotchiId, LoyaltyAccount>,
+    // exclusive attributes bought with loyalty points only
+    points_prices: BTreeMap<AttributeId, u128>,
+    referral_percent: u8,
+    // tamagotchis that have bought, redeemed or crafted anything, so referrals are only rewarded once
+    customers: BTreeSet<TamagotchiId>,
+    referred_by: BTreeMap<TamagotchiId, TamagotchiId>,
+    // number of rewarded referrals and the rewards per token of each referrer
+    referrals: BTreeMap<TamagotchiId, u32>,
+    referral_rewards: BTreeMap<(TamagotchiId, ActorId), Price>,
+    // purchase messages waiting for the FT contract reply
+    awaiting: BTreeMap<MessageId, (TamagotchiId, TransactionId)>,
+    // refunds, withdrawals and payouts that aren't settled yet
+    transfers: BTreeMap<TransactionId, OutgoingTransfer>,
+    // messages waiting for a transfer out of the store
+    awaiting_transfers: BTreeMap<MessageId, TransactionId>,
+    // buyers whose transactions ran out of gas and may have been paid for
+    reconciliations: BTreeSet<TamagotchiId>,
+    // buyers whose purchase is being settled, other messages can run while it's awaited
+    locked: BTreeSet<TamagotchiId>,
+    frozen: bool,
+    // the state being imported and the number of chunks received
+    import: Vec<u8>,
+    imported_chunks: u32,
+    native_prices: BTreeMap<AttributeId, u128>,
+    native_revenue: u128,
+    native_treasury: u128,
+    roles: BTreeMap<ActorId, BTreeSet<Role>>,
+    paused: bool,
+}
+
+impl AttributeStore {
+    fn create_attribute(
+        &mut self,
+        attribute_id: AttributeId,
+        metadata: &AttrMetadata,
+        price: Price,
+    ) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can add attributes",
+        );
+
+        if self
+            .attributes
+            .insert(attribute_id, (metadata.clone(), price))
+            .is_some()
+        {
+            panic!("Attribute with that ID already exists");
+        }
+
+        msg::reply(StoreEvent::AttributeCreated { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeCreated");
+    }
+    async fn buy_attribute(
+        &mut self,
+        attribute_id: AttributeId,
+        pay_with: Option<ActorId>,
+        coupon: Option<CouponCode>,
+        points: u128,
+        referrer: Option<TamagotchiId>,
+        recipient: &TamagotchiId,
+    ) {
+        let tmg_id = msg::source();
+        if !self.whitelisted(attribute_id, recipient) {
+            msg::reply(StoreEvent::Error(StoreError::WhitelistOnly), msg::value())
+                .expect("Error in sending a reply `StoreEvent::Error`");
+            return;
+        }
+
+        if let Some(referrer) = referrer {
+            assert!(
+                referrer != tmg_id && referrer != *recipient,
+                "Tamagotchi can't refer itself"
+            );
+        }
+
+        if msg::value() > 0 {
+            assert_eq!(
+                points, 0,
+                "Loyalty points can only be spent on the main fungible token"
+            );
+            assert!(
+                referrer.is_none(),
+                "Referrals are only rewarded for fungible token purchases"
+            );
+            self.buy_attribute_natively(attribute_id, coupon, recipient);
+            return;
+        }
+
+        if let Some(tx) = self.transactions.get(&tmg_id) {
+            // if `tx.attribute_id` is not equal to `attribute_id` then it means that transaction didn`t completed
+            // we ask the tamagotchi contract to complete the previous transaction,
+            // the same goes for a pending gift, so it isn't delivered to another recipient
+            if attribute_id != tx.attribute_id || tx.recipient != *recipient {
+                msg::reply(
+                    StoreEvent::CompletePrevTx {
+                        attribute_id: tx.attribute_id,
+                    },
+                    0,
+                )
+                .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
+                return;
+            }
+        } else {
+            self.check_can_receive(recipient, attribute_id);
+            let token = pay_with.unwrap_or(self.ft_contract_id);
+            let price = self.token_price(attribute_id, &token);
+            let price = self.quote_price(attribute_id, price, coupon.as_ref());
+            if points > 0 {
+                assert_eq!(
+                    token, self.ft_contract_id,
+                    "Loyalty points can only be spent on the main fungible token"
+                );
+                assert!(
+                    self.loyalty_points(&tmg_id) >= points,
+                    "Not enough loyalty points"
+                );
+            }
+            let points = points.min(price);
+            let price = price - points;
+            // the coupon use and the points are reserved, so pending transactions can't overuse them
+            if let Some(code) = &coupon {
+                self.redeem_coupon(code);
+            }
+            self.spend_points(&tmg_id, points);
+
+            let transaction_id = self.next_transaction_id();
+            self.transactions.insert(
+                tmg_id,
+                Transaction {
+                    id: transaction_id,
+                    attribute_id,
+                    created_at: exec::block_height(),
+                    token,
+                    price,
+                    coupon,
+                    recipient: *recipient,
+                    points,
+                    referrer,
+                },
+            );
+            self.schedule_tx_expiry(&tmg_id, transaction_id);
+        }
+
+        self.complete_tx(&tmg_id).await;
+    }
+
+    fn buy_attribute_natively(
+        &mut self,
+        attribute_id: AttributeId,
+        coupon: Option<CouponCode>,
+        recipient: &TamagotchiId,
+    ) {
+        let price = *self
+            .native_prices
+            .get(&attribute_id)
+            .expect("Attribute can't be bought with native value");
+        let price = self.quote_price(attribute_id, price, coupon.as_ref());
+        self.check_can_receive(recipient, attribute_id);
+        let value = msg::value();
+        assert!(value >= price, "Not enough value to buy the attribute");
+
+        if let Some(code) = &coupon {
+            self.redeem_coupon(code);
+        }
+        // native purchases don't create pending transactions,
+        // the id is only taken to draw mystery boxes
+        let transaction_id = self.next_transaction_id();
+        self.deliver(
+            &msg::source(),
+            recipient,
+            attribute_id,
+            Some(Purchase {
+                block: exec::block_height(),
+                price,
+                currency: Currency::Native,
+                buyer: msg::source(),
+                points: 0,
+                referral_reward: 0,
+            }),
+            transaction_id,
+        )
+        .expect("Mystery box has nothing to draw");
+        self.customers.insert(msg::source());
+        self.native_revenue += price;
+        self.native_treasury += price;
+        self.raise_curve_price(attribute_id);
+        self.record_receipt(Receipt {
+            transaction_id,
+            buyer: msg::source(),
+            attribute_id,
+            price,
+            currency: Currency::Native,
+            block: exec::block_height(),
+            success: true,
+        });
+
+        // the change is returned along with the reply the buyer is waiting for
+        msg::reply(self.sold_event(transaction_id, true), value - price)
+            .expect("Error in sending a reply `StoreEvent::AttributeSold`");
+    }
+
+    async fn retry_tx(&mut self, tmg_id: &TamagotchiId) {
+        let source = msg::source();
+        assert!(
+            source == *tmg_id || self.has_role(&source, Role::Operator),
+            "Only the buyer or operator can retry transactions"
+        );
+        assert!(
+            self.transactions.contains_key(tmg_id),
+            "There is no pending transaction"
+        );
+        self.complete_tx(tmg_id).await;
+    }
+
+    // the pending transaction keeps its id between attempts,
+    // so the FT contract doesn't charge the tamagotchi twice
+    async fn complete_tx(&mut self, tmg_id: &TamagotchiId) {
+        assert!(
+            self.locked.insert(*tmg_id),
+            "Tamagotchi has a purchase in progress"
+        );
+        self.check_invariants();
+        let tx = self.transactions[tmg_id].clone();
+        self.awaiting.insert(msg::id(), (*tmg_id, tx.id));
+        let result = self.sell_attribute(tmg_id, &tx).await;
+        self.awaiting.remove(&msg::id());
+        self.locked.remove(tmg_id);
+        self.transactions.remove(tmg_id);
+        self.reconciliations.remove(tmg_id);
+        if !result {
+            self.release_reservations(tmg_id, &tx);
+        }
+        let first_purchase = result && self.customers.insert(*tmg_id);
+        if let Some(referrer) = tx.referrer.filter(|_| first_purchase) {
+            let reward = self.reward_referrer(tmg_id, &referrer, &tx.token, tx.price);
+            if let Some(purchase) = self.purchases.get_mut(&(tx.recipient, tx.attribute_id)) {
+                purchase.referral_reward = reward;
+            }
+        }
+        self.record_receipt(Receipt {
+            transaction_id: tx.id,
+            buyer: *tmg_id,
+            attribute_id: tx.attribute_id,
+            price: tx.price,
+            currency: Currency::FungibleToken(tx.token),
+            block: exec::block_height(),
+            success: result,
+        });
+
+        msg::reply(self.sold_event(tx.id, result), 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeSold`");
+    }
+
+    async fn reconcile_tx(&mut self, tmg_id: &TamagotchiId) {
+        let source = msg::source();
+        assert!(
+            source == *tmg_id || self.has_role(&source, Role::Operator),
+            "Only the buyer or operator can reconcile transactions"
+        );
+        assert!(
+            self.reconciliations.contains(tmg_id),
+            "Transaction doesn't need reconciliation"
+        );
+        self.complete_tx(tmg_id).await;
+    }
+
+    // called from `handle_signal` when a purchase or a craft has run out of gas,
+    // a pending craft is simply retried with `Craft`
+    fn mark_for_reconciliation(&mut self, message_id: &MessageId) {
+        if let Some((tmg_id, transaction_id)) = self.awaiting.remove(message_id) {
+            self.locked.remove(&tmg_id);
+            if self
+                .transactions
+                .get(&tmg_id)
+                .is_some_and(|tx| tx.id == transaction_id)
+            {
+                self.reconciliations.insert(tmg_id);
+            }
+        }
+    }
+
+    // checked before any tokens are taken from a tamagotchi,
+    // so a broken store fails the message instead of charging for nothing
+    fn check_invariants(&self) {
+        assert!(
+            self.locked.iter().all(|tmg_id| {
+                self.transactions.contains_key(tmg_id) || self.crafts.contains_key(tmg_id)
+            }),
+            "Locked tamagotchi has nothing pending"
+        );
+        for (token, total) in &self.total_revenue {
+            let sum: Price = self
+                .revenue
+                .iter()
+                .filter(|((_, revenue_token), _)| revenue_token == token)
+                .map(|(_, revenue)| revenue)
+                .sum();
+            assert_eq!(sum, *total, "Revenue doesn't add up to the total revenue");
+        }
+    }
+
+    // what a pending purchase delivers can't change before it's settled
+    fn check_not_pending(&self, attribute_id: AttributeId) {
+        assert!(
+            !self
+                .transactions
+                .values()
+                .any(|tx| tx.attribute_id == attribute_id),
+            "Attribute has pending purchases"
+        );
+    }
+
+    fn snapshot(&self) -> store_io::AttributeStore {
+        store_io::AttributeStore {
+            admin: self.admin,
+            ft_contract_id: self.ft_contract_id,
+            attributes: self.attributes.clone(),
+            owners: self.owners.clone(),
+            transaction_id: self.transaction_id,
+            transactions: self.transactions.clone(),
+            tx_timeout: self.tx_timeout,
+            refund_policy: self.refund_policy,
+            purchases: self.purchases.clone(),
+            accepted_tokens: self.accepted_tokens.clone(),
+            token_prices: self.token_prices.clone(),
+            revenue: self.revenue.clone(),
+            total_revenue: self.total_revenue.clone(),
+            treasury: self.treasury.clone(),
+            revenue_splits: self.revenue_splits.clone(),
+            payouts: self.payouts.clone(),
+            coupons: self.coupons.clone(),
+            availability: self.availability.clone(),
+            sales: self.sales.clone(),
+            bonding_curves: self.bonding_curves.clone(),
+            trade_id: self.trade_id,
+            trades: self.trades.clone(),
+            effects: self.effects.clone(),
+            consumables: self.consumables.clone(),
+            inventory: self.inventory.clone(),
+            mystery_boxes: self.mystery_boxes.clone(),
+            draws: self.draws.clone(),
+            recipes: self.recipes.clone(),
+            crafts: self.crafts.clone(),
+            presales: self.presales.clone(),
+            whitelists: self.whitelists.clone(),
+            receipts: self.receipts.clone(),
+            sale_count: self.sale_count,
+            loyalty_policy: self.loyalty_policy,
+            loyalty: self.loyalty.clone(),
+            points_prices: self.points_prices.clone(),
+            referral_percent: self.referral_percent,
+            customers: self.customers.clone(),
+            referred_by: self.referred_by.clone(),
+            referrals: self.referrals.clone(),
+            referral_rewards: self.referral_rewards.clone(),
+            awaiting: self.awaiting.clone(),
+            reconciliations: self.reconciliations.clone(),
+            locked: self.locked.clone(),
+            frozen: self.frozen,
+            native_prices: self.native_prices.clone(),
+            native_revenue: self.native_revenue,
+            native_treasury: self.native_treasury,
+            roles: self.roles.clone(),
+            paused: self.paused,
+            transfers: self.transfers.clone(),
+        }
+    }
+
+    fn from_snapshot(state: store_io::AttributeStore) -> Self {
+        Self {
+            admin: state.admin,
+            ft_contract_id: state.ft_contract_id,
+            attributes: state.attributes,
+            owners: state.owners,
+            transaction_id: state.transaction_id,
+            transactions: state.transactions,
+            tx_timeout: state.tx_timeout,
+            refund_policy: state.refund_policy,
+            purchases: state.purchases,
+            accepted_tokens: state.accepted_tokens,
+            token_prices: state.token_prices,
+            revenue: state.revenue,
+            total_revenue: state.total_revenue,
+            treasury: state.treasury,
+            revenue_splits: state.revenue_splits,
+            payouts: state.payouts,
+            coupons: state.coupons,
+            availability: state.availability,
+            sales: state.sales,
+            bonding_curves: state.bonding_curves,
+            trade_id: state.trade_id,
+            trades: state.trades,
+            effects: state.effects,
+            consumables: state.consumables,
+            inventory: state.inventory,
+            mystery_boxes: state.mystery_boxes,
+            draws: state.draws,
+            recipes: state.recipes,
+            crafts: state.crafts,
+            presales: state.presales,
+            whitelists: state.whitelists,
+            receipts: state.receipts,
+            sale_count: state.sale_count,
+            loyalty_policy: state.loyalty_policy,
+            loyalty: state.loyalty,
+            points_prices: state.points_prices,
+            referral_percent: state.referral_percent,
+            customers: state.customers,
+            referred_by: state.referred_by,
+            referrals: state.referrals,
+            referral_rewards: state.referral_rewards,
+            awaiting: state.awaiting,
+            reconciliations: state.reconciliations,
+            locked: state.locked,
+            frozen: state.frozen,
+            native_prices: state.native_prices,
+            native_revenue: state.native_revenue,
+            native_treasury: state.native_treasury,
+            roles: state.roles,
+            paused: state.paused,
+            transfers: state.transfers,
+            ..Default::default()
+        }
+    }
+
+    fn set_frozen(&mut self, frozen: bool) {
+        self.check_role(Role::Owner, "Only owner can freeze the store");
+        self.frozen = frozen;
+        let event = if frozen {
+            StoreEvent::Frozen
+        } else {
+            StoreEvent::Unfrozen
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Frozen`");
+    }
+
+    // the state is encoded again for every chunk, it can't change while the store is frozen
+    fn export_state(&self, index: u32) {
+        self.check_role(Role::Owner, "Only owner can export the state");
+        assert!(self.frozen, "The store must be frozen to export its state");
+        // the new store can't finish them, the FT contract only knows this one,
+        // transfers that ran out of gas have to be reconciled too
+        assert!(
+            self.transactions.is_empty() && self.crafts.is_empty() && self.transfers.is_empty(),
+            "There are pending transactions"
+        );
+        // the tokens stay with this store, so the new one must not think it holds them,
+        // payouts that beneficiaries don't claim are pushed out with `PayOut`
+        assert!(
+            self.treasury.values().all(|amount| *amount == 0)
+                && self.payouts.values().all(|amount| *amount == 0)
+                && self.native_treasury == 0,
+            "Treasury and payouts must be withdrawn before the export"
+        );
+
+        let data = self.snapshot().encode();
+        let chunk = StateChunk {
+            version: STATE_VERSION,
+            index,
+            total: data.chunks(STATE_CHUNK_SIZE).len() as u32,
+            checksum: store_io::checksum(&data),
+            data: data
+                .chunks(STATE_CHUNK_SIZE)
+                .nth(index as usize)
+                .expect("Chunk index is out of range")
+                .to_vec(),
+        };
+        msg::reply(StoreEvent::StateExported { chunk }, 0)
+            .expect("Error in sending a reply `StoreEvent::StateExported`");
+    }
+
+    fn import_state(&mut self, chunk: StateChunk) {
+        self.check_role(Role::Owner, "Only owner can import the state");
+        assert!(self.frozen, "The store must be frozen to import a state");
+        assert!(
+            (store_io::v1::STATE_VERSION..=STATE_VERSION).contains(&chunk.version),
+            "Unsupported state version"
+        );
+        // the first chunk restarts an import that has failed midway
+        if chunk.index == 0 {
+            self.import.clear();
+            self.imported_chunks = 0;
+        }
+        assert_eq!(
+            chunk.index, self.imported_chunks,
+            "Chunks must be imported in order"
+        );
+
+        let index = chunk.index;
+        self.import.extend(chunk.data);
+        self.imported_chunks += 1;
+        let complete = self.imported_chunks == chunk.total;
+        if complete {
+            let data = core::mem::take(&mut self.import);
+            assert_eq!(
+                store_io::checksum(&data),
+                chunk.checksum,
+                "State checksum doesn't match"
+            );
+            let state = if chunk.version == STATE_VERSION {
+                store_io::AttributeStore::decode(&mut &data[..])
+                    .expect("Unable to decode the imported state")
+            } else {
+                let state = store_io::v1::AttributeStore::decode(&mut &data[..])
+                    .expect("Unable to decode the imported state");
+                assert!(
+                    state.transactions.is_empty(),
+                    "Pending transactions must be removed from the old store"
+                );
+                store_io::AttributeStore {
+                    tx_timeout: DEFAULT_TX_TIMEOUT,
+                    ..state.into()
+                }
+            };
+            *self = Self::from_snapshot(state);
+            // stays frozen until the owner checks the imported state
+            self.frozen = true;
+        }
+        msg::reply(StoreEvent::StateImported { index, complete }, 0)
+            .expect("Error in sending a reply `StoreEvent::StateImported`");
+    }
+
+    fn record_receipt(&mut self, receipt: Receipt) {
+        if receipt.success {
+            self.sale_count += 1;
+        }
+        let receipts = self.receipts.entry(receipt.buyer).or_default();
+        if receipts.len() == MAX_RECEIPTS {
+            receipts.remove(0);
+        }
+        receipts.push(receipt);
+    }
+
+    fn sold_event(&self, transaction_id: TransactionId, success: bool) -> StoreEvent {
+        match self.draws.get(&transaction_id) {
+            Some(draw) if success => StoreEvent::MysteryBoxOpened {
+                attribute_id: draw.attribute_id,
+            },
+            _ => StoreEvent::AttributeSold { success },
+        }
+    }
+
+    fn schedule_tx_expiry(&self, tmg_id: &TamagotchiId, transaction_id: TransactionId) {
+        if self.tx_timeout == 0 {
+            return;
+        }
+        msg::send_delayed(
+            exec::program_id(),
+            StoreAction::ExpireTx {
+                tamagotchi_id: *tmg_id,
+                transaction_id,
+            },
+            0,
+            self.tx_timeout,
+        )
+        .expect("Error in sending a delayed message `StoreAction::ExpireTx`");
+    }
+
+    fn expire_tx(&mut self, tmg_id: &TamagotchiId, transaction_id: TransactionId) {
+        assert_eq!(
+            msg::source(),
+            exec::program_id(),
+            "Only the store can expire transactions"
+        );
+        // the transaction may have been completed or replaced by a newer one in the meantime,
+        // the ones being settled or waiting for reconciliation may already be paid for and are kept
+        if !self.locked.contains(tmg_id)
+            && !self.reconciliations.contains(tmg_id)
+            && self
+                .transactions
+                .get(tmg_id)
+                .is_some_and(|tx| tx.id == transaction_id)
+        {
+            self.drop_tx(tmg_id);
+        }
+    }
+
+    fn drop_tx(&mut self, tmg_id: &TamagotchiId) {
+        if let Some(tx) = self.transactions.remove(tmg_id) {
+            self.release_reservations(tmg_id, &tx);
+        }
+    }
+
+    fn set_tx_timeout(&mut self, timeout: BlockNumber) {
+        self.check_role(Role::Operator, "Only operator can set transaction timeout");
+        self.tx_timeout = timeout;
+        msg::reply(StoreEvent::TxTimeoutSet { timeout }, 0)
+            .expect("Error in sending a reply `StoreEvent::TxTimeoutSet`");
+    }
+
+    async fn sell_attribute(&mut self, tmg_id: &TamagotchiId, tx: &Transaction) -> bool {
+        if transfer_tokens(tx.id, &tx.token, tmg_id, &exec::program_id(), tx.price)
+            .await
+            .is_ok()
+        {
+            // points are worth a unit of the main token, so other tokens don't earn them
+            let points = if tx.token == self.ft_contract_id {
+                self.earned_points(tx.price)
+            } else {
+                0
+            };
+            let delivered = self.deliver(
+                tmg_id,
+                &tx.recipient,
+                tx.attribute_id,
+                Some(Purchase {
+                    block: exec::block_height(),
+                    price: tx.price,
+                    currency: Currency::FungibleToken(tx.token),
+                    buyer: *tmg_id,
+                    points,
+                    referral_reward: 0,
+                }),
+                tx.id,
+            );
+            // the recipient got the attribute or everything in the box another way
+            // while the transaction was pending, the price can be claimed back like revenue
+            if delivered.is_none() {
+                *self.payouts.entry((*tmg_id, tx.token)).or_default() += tx.price;
+                return false;
+            }
+            self.record_sale(tx.attribute_id, &tx.token, tx.price);
+            self.raise_curve_price(tx.attribute_id);
+            self.award_points(tmg_id, points);
+            return true;
+        }
+        false
+    }
+
+    // called on the first purchase of a tamagotchi,
+    // the reward is paid out of the store's share of the price
+    // and claimed by the referrer like revenue
+    fn reward_referrer(
+        &mut self,
+        tmg_id: &TamagotchiId,
+        referrer: &TamagotchiId,
+        token: &ActorId,
+        price: Price,
+    ) -> Price {
+        self.referred_by.insert(*tmg_id, *referrer);
+        *self.referrals.entry(*referrer).or_default() += 1;
+
+        let treasury = self.treasury.get(token).copied().unwrap_or_default();
+        let reward = (price * Price::from(self.referral_percent) / 100).min(treasury);
+        if reward == 0 {
+            return 0;
+        }
+        self.take_from_treasury(token, reward);
+        *self
+            .referral_rewards
+            .entry((*referrer, *token))
+            .or_default() += reward;
+        *self.payouts.entry((*referrer, *token)).or_default() += reward;
+        reward
+    }
+
+    // only the part of the reward the referrer hasn't claimed yet goes back to the treasury,
+    // returns how much was taken back
+    fn claw_back_reward(&mut self, purchase: &Purchase, token: &ActorId) -> Price {
+        let Some(referrer) = self.referred_by.get(&purchase.buyer).copied() else {
+            return 0;
+        };
+        let Some(payout) = self.payouts.get_mut(&(referrer, *token)) else {
+            return 0;
+        };
+        let reward = purchase.referral_reward.min(*payout);
+        *payout -= reward;
+        if *payout == 0 {
+            self.payouts.remove(&(referrer, *token));
+        }
+        if let Some(rewards) = self.referral_rewards.get_mut(&(referrer, *token)) {
+            *rewards = rewards.saturating_sub(reward);
+        }
+        *self.treasury.entry(*token).or_default() += reward;
+        reward
+    }
+
+    fn set_referral_reward(&mut self, percent: u8) {
+        self.check_role(Role::Treasurer, "Only treasurer can set referral reward");
+        assert!(percent <= 100, "Referral reward can't exceed 100 percent");
+        self.referral_percent = percent;
+        msg::reply(StoreEvent::ReferralRewardSet { percent }, 0)
+            .expect("Error in sending a reply `StoreEvent::ReferralRewardSet`");
+    }
+
+    // checks that the attribute can be bought right now
+    // and applies the ongoing sale and the coupon to its price
+    fn quote_price(
+        &self,
+        attribute_id: AttributeId,
+        price: Price,
+        coupon: Option<&CouponCode>,
+    ) -> Price {
+        self.check_availability(attribute_id);
+        let now = exec::block_height();
+        let price = match self.bonding_curves.get(&attribute_id) {
+            Some(curve) => price.saturating_mul(curve.multiplier) / BASE_MULTIPLIER,
+            None => price,
+        };
+        let presale_discount = self
+            .presales
+            .get(&attribute_id)
+            .filter(|presale| now <= presale.ends_at)
+            .and_then(|presale| presale.discount);
+        let price = self
+            .sales
+            .get(&attribute_id)
+            .into_iter()
+            .flatten()
+            .filter(|sale| (sale.starts_at..=sale.ends_at).contains(&now))
+            .map(|sale| sale.discount)
+            .chain(presale_discount)
+            .map(|discount| apply_discount(price, discount))
+            .min()
+            .unwrap_or(price);
+        self.discounted_price(attribute_id, price, coupon)
+    }
+
+    // expired points are worth nothing
+    fn loyalty_points(&self, tmg_id: &TamagotchiId) -> u128 {
+        match self.loyalty.get(tmg_id) {
+            Some(account) if exec::block_height() <= account.expires_at => account.points,
+            _ => 0,
+        }
+    }
+
+    // the points could have been spent or have expired since they were awarded,
+    // returns how many were actually taken
+    fn spend_points(&mut self, tmg_id: &TamagotchiId, points: u128) -> u128 {
+        let available = self.loyalty_points(tmg_id);
+        let spent = points.min(available);
+        if spent == 0 {
+            return 0;
+        }
+        if let Some(account) = self.loyalty.get_mut(tmg_id) {
+            account.points = available - spent;
+        }
+        spent
+    }
+
+    // reserved points go back with the expiry the account has now
+    fn restore_points(&mut self, tmg_id: &TamagotchiId, points: u128) {
+        if points == 0 {
+            return;
+        }
+        let restored = self.loyalty_points(tmg_id).saturating_add(points);
+        if let Some(account) = self.loyalty.get_mut(tmg_id) {
+            account.points = restored;
+        }
+    }
+
+    fn earned_points(&self, price: Price) -> u128 {
+        price.saturating_mul(self.loyalty_policy.percent.into()) / 100
+    }
+
+    // every purchase pushes the expiry of all the points back
+    fn award_points(&mut self, tmg_id: &TamagotchiId, earned: u128) {
+        if earned == 0 {
+            return;
+        }
+        let expires_at = match self.loyalty_policy.expiry {
+            0 => BlockNumber::MAX,
+            expiry => exec::block_height().saturating_add(expiry),
+        };
+        let points = self.loyalty_points(tmg_id).saturating_add(earned);
+        self.loyalty
+            .insert(*tmg_id, LoyaltyAccount { points, expires_at });
+    }
+
+    fn set_loyalty_policy(&mut self, percent: u8, expiry: BlockNumber) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set loyalty policy",
+        );
+        assert!(percent <= 100, "Loyalty points can't exceed 100 percent");
+        self.loyalty_policy = LoyaltyPolicy { percent, expiry };
+        msg::reply(StoreEvent::LoyaltyPolicySet { percent, expiry }, 0)
+            .expect("Error in sending a reply `StoreEvent::LoyaltyPolicySet`");
+    }
+
+    fn set_points_price(&mut self, attribute_id: AttributeId, points: Option<u128>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set points prices",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        match points {
+            Some(points) => {
+                assert!(points > 0, "Points price must be positive");
+                self.points_prices.insert(attribute_id, points);
+            }
+            None => {
+                self.points_prices.remove(&attribute_id);
+            }
+        }
+        msg::reply(StoreEvent::PointsPriceSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::PointsPriceSet`");
+    }
+
+    fn redeem_points(&mut self, attribute_id: AttributeId) {
+        let tmg_id = msg::source();
+        let points = *self
+            .points_prices
+            .get(&attribute_id)
+            .expect("Attribute can't be redeemed for loyalty points");
+        if !self.whitelisted(attribute_id, &tmg_id) {
+            msg::reply(StoreEvent::Error(StoreError::WhitelistOnly), 0)
+                .expect("Error in sending a reply `StoreEvent::Error`");
+            return;
+        }
+        self.check_availability(attribute_id);
+        self.check_can_receive(&tmg_id, attribute_id);
+        assert!(
+            self.loyalty_points(&tmg_id) >= points,
+            "Not enough loyalty points"
+        );
+
+        self.spend_points(&tmg_id, points);
+        self.customers.insert(tmg_id);
+        let transaction_id = self.next_transaction_id();
+        let attribute_id = self
+            .deliver(&tmg_id, &tmg_id, attribute_id, None, transaction_id)
+            .expect("Mystery box has nothing to draw");
+        msg::reply(
+            StoreEvent::PointsRedeemed {
+                attribute_id,
+                points,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::PointsRedeemed`");
+    }
+
+    fn check_availability(&self, attribute_id: AttributeId) {
+        let now = exec::block_height();
+        let availability = self
+            .availability
+            .get(&attribute_id)
+            .copied()
+            .unwrap_or_default();
+        if let Some(from) = availability.available_from {
+            assert!(now >= from, "Attribute isn't on sale yet");
+        }
+        if let Some(until) = availability.available_until {
+            assert!(now <= until, "Attribute is no longer on sale");
+        }
+    }
+
+    fn raise_curve_price(&mut self, attribute_id: AttributeId) {
+        if let Some(curve) = self.bonding_curves.get_mut(&attribute_id) {
+            curve.multiplier = match curve.growth {
+                CurveGrowth::Linear { percent } => curve
+                    .multiplier
+                    .saturating_add(u128::from(percent) * BASE_MULTIPLIER / 100),
+                CurveGrowth::Exponential { percent } => {
+                    curve.multiplier.saturating_mul(100 + u128::from(percent)) / 100
+                }
+            };
+        }
+    }
+
+    fn set_bonding_curve(&mut self, attribute_id: AttributeId, growth: Option<CurveGrowth>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set bonding curves",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        match growth {
+            Some(growth) => self.bonding_curves.insert(
+                attribute_id,
+                BondingCurve {
+                    growth,
+                    multiplier: BASE_MULTIPLIER,
+                },
+            ),
+            None => self.bonding_curves.remove(&attribute_id),
+        };
+        msg::reply(StoreEvent::BondingCurveSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::BondingCurveSet`");
+    }
+
+    fn discounted_price(
+        &self,
+        attribute_id: AttributeId,
+        price: Price,
+        coupon: Option<&CouponCode>,
+    ) -> Price {
+        let Some(code) = coupon else {
+            return price;
+        };
+        let coupon = self.coupons.get(code).expect("Coupon doesn't exist");
+        assert!(coupon.uses < coupon.max_uses, "Coupon has been used up");
+        assert!(
+            exec::block_height() <= coupon.expires_at,
+            "Coupon has expired"
+        );
+        if let Some(coupon_attribute_id) = coupon.attribute_id {
+            assert_eq!(
+                coupon_attribute_id, attribute_id,
+                "Coupon isn't valid for that attribute"
+            );
+        }
+
+        apply_discount(price, coupon.discount)
+    }
+
+    // the coupon has just been checked by `discounted_price`
+    fn redeem_coupon(&mut self, code: &CouponCode) {
+        if let Some(coupon) = self.coupons.get_mut(code) {
+            coupon.uses += 1;
+        }
+    }
+
+    // gives back the coupon use and the points reserved by a transaction that wasn't paid
+    fn release_reservations(&mut self, tmg_id: &TamagotchiId, tx: &Transaction) {
+        self.restore_points(tmg_id, tx.points);
+        if let Some(coupon) = tx
+            .coupon
+            .as_ref()
+            .and_then(|code| self.coupons.get_mut(code))
+        {
+            coupon.uses = coupon.uses.saturating_sub(1);
+        }
+    }
+
+    fn create_coupon(
+        &mut self,
+        code: CouponCode,
+        discount: Discount,
+        max_uses: u32,
+        expires_at: BlockNumber,
+        attribute_id: Option<AttributeId>,
+    ) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can create coupons",
+        );
+        check_discount(discount);
+        assert!(
+            !self.coupons.contains_key(&code),
+            "Coupon with that code already exists"
+        );
+
+        self.coupons.insert(
+            code.clone(),
+            Coupon {
+                discount,
+                max_uses,
+                uses: 0,
+                expires_at,
+                attribute_id,
+            },
+        );
+        msg::reply(StoreEvent::CouponCreated { code }, 0)
+            .expect("Error in sending a reply `StoreEvent::CouponCreated`");
+    }
+
+    fn set_availability(
+        &mut self,
+        attribute_id: AttributeId,
+        available_from: Option<BlockNumber>,
+        available_until: Option<BlockNumber>,
+    ) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set availability",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        if available_from.is_none() && available_until.is_none() {
+            self.availability.remove(&attribute_id);
+        } else {
+            self.availability.insert(
+                attribute_id,
+                Availability {
+                    available_from,
+                    available_until,
+                },
+            );
+        }
+        msg::reply(StoreEvent::AvailabilitySet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::AvailabilitySet`");
+    }
+
+    fn schedule_sale(&mut self, attribute_id: AttributeId, sale: Sale) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can schedule sales",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        assert!(
+            sale.starts_at <= sale.ends_at,
+            "Sale can't end before it starts"
+        );
+        check_discount(sale.discount);
+
+        let now = exec::block_height();
+        let sales = self.sales.entry(attribute_id).or_default();
+        // the finished sales are dropped to keep the schedule short
+        sales.retain(|sale| sale.ends_at >= now);
+        sales.push(sale);
+        msg::reply(StoreEvent::SaleScheduled { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::SaleScheduled`");
+    }
+
+    fn in_presale(&self, attribute_id: AttributeId) -> bool {
+        self.presales
+            .get(&attribute_id)
+            .is_some_and(|presale| exec::block_height() <= presale.ends_at)
+    }
+
+    // gifts are checked too, so the whitelist can't be bypassed by a whitelisted buyer
+    fn whitelisted(&self, attribute_id: AttributeId, recipient: &TamagotchiId) -> bool {
+        !self.in_presale(attribute_id)
+            || self
+                .whitelists
+                .get(&attribute_id)
+                .is_some_and(|whitelist| whitelist.contains(recipient))
+    }
+
+    fn set_presale(&mut self, attribute_id: AttributeId, presale: Option<Presale>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set presales",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        match presale {
+            Some(presale) => {
+                if let Some(discount) = presale.discount {
+                    check_discount(discount);
+                }
+                self.presales.insert(attribute_id, presale);
+            }
+            None => {
+                self.presales.remove(&attribute_id);
+                self.whitelists.remove(&attribute_id);
+            }
+        }
+        msg::reply(StoreEvent::PresaleSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::PresaleSet`");
+    }
+
+    fn update_whitelist(
+        &mut self,
+        attribute_id: AttributeId,
+        tamagotchis: Vec<TamagotchiId>,
+        add: bool,
+    ) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can update the whitelist",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        let whitelist = self.whitelists.entry(attribute_id).or_default();
+        for tmg_id in tamagotchis {
+            if add {
+                whitelist.insert(tmg_id);
+            } else {
+                whitelist.remove(&tmg_id);
+            }
+        }
+        if whitelist.is_empty() {
+            self.whitelists.remove(&attribute_id);
+        }
+        msg::reply(StoreEvent::WhitelistUpdated { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::WhitelistUpdated`");
+    }
+
+    fn clear_sales(&mut self, attribute_id: AttributeId) {
+        self.check_role(Role::CatalogManager, "Only catalog manager can clear sales");
+        self.sales.remove(&attribute_id);
+        msg::reply(StoreEvent::SalesCleared { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::SalesCleared`");
+    }
+
+    fn remove_coupon(&mut self, code: CouponCode) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can remove coupons",
+        );
+        self.coupons.remove(&code);
+        msg::reply(StoreEvent::CouponRemoved { code }, 0)
+            .expect("Error in sending a reply `StoreEvent::CouponRemoved`");
+    }
+
+    fn deliver(
+        &mut self,
+        buyer: &TamagotchiId,
+        recipient: &TamagotchiId,
+        attribute_id: AttributeId,
+        purchase: Option<Purchase>,
+        transaction_id: TransactionId,
+    ) -> Option<AttributeId> {
+        // the box is opened only once it's paid for, so a failed payment can't be used to reroll it
+        let (attribute_id, purchase) = match self.mystery_boxes.get(&attribute_id) {
+            Some(pool) => {
+                let pool = self.drawable(recipient, pool);
+                if pool.is_empty() {
+                    return None;
+                }
+                let drawn_id = draw(&pool, transaction_id);
+                self.draws.insert(
+                    transaction_id,
+                    Draw {
+                        tamagotchi_id: *recipient,
+                        box_id: attribute_id,
+                        attribute_id: drawn_id,
+                    },
+                );
+                // the price was paid for the box, so the drawn attribute can't be sold back
+                (drawn_id, None)
+            }
+            None => (attribute_id, purchase),
+        };
+
+        if self.consumables.contains_key(&attribute_id) {
+            *self
+                .inventory
+                .entry((*recipient, attribute_id))
+                .or_default() += 1;
+        } else if self.owns(recipient, attribute_id) {
+            // it was received another way while the payment was pending
+            return None;
+        } else {
+            self.add_owned(recipient, attribute_id, purchase);
+        }
+        if recipient != buyer {
+            msg::send(
+                *recipient,
+                StoreEvent::GiftReceived {
+                    attribute_id,
+                    from: *buyer,
+                },
+                0,
+            )
+            .expect("Error in sending a message `StoreEvent::GiftReceived`");
+        }
+        Some(attribute_id)
+    }
+
+    // attributes of the pool the tamagotchi can still get, consumables stack
+    fn drawable(
+        &self,
+        tmg_id: &TamagotchiId,
+        pool: &[(AttributeId, u32)],
+    ) -> Vec<(AttributeId, u32)> {
+        pool.iter()
+            .filter(|(attribute_id, _)| {
+                self.consumables.contains_key(attribute_id) || !self.holds(tmg_id, *attribute_id)
+            })
+            .copied()
+            .collect()
+    }
+
+    fn token_price(&self, attribute_id: AttributeId, token: &ActorId) -> Price {
+        if *token == self.ft_contract_id {
+            let (_, price) = self
+                .attributes
+                .get(&attribute_id)
+                .expect("Can`t get attribute_id");
+            return *price;
+        }
+        assert!(
+            self.accepted_tokens.contains(token),
+            "The store doesn't accept that token"
+        );
+        *self
+            .token_prices
+            .get(&(attribute_id, *token))
+            .expect("Attribute has no price in that token")
+    }
+
+    fn record_sale(&mut self, attribute_id: AttributeId, token: &ActorId, price: Price) {
+        *self.revenue.entry((attribute_id, *token)).or_default() += price;
+        *self.total_revenue.entry(*token).or_default() += price;
+
+        let mut treasury_share = price;
+        if let Some(beneficiaries) = self.revenue_splits.get(&attribute_id) {
+            for (beneficiary, percent) in beneficiaries {
+                let share = price * Price::from(*percent) / 100;
+                *self.payouts.entry((*beneficiary, *token)).or_default() += share;
+                treasury_share -= share;
+            }
+        }
+        *self.treasury.entry(*token).or_default() += treasury_share;
+    }
+
+    async fn sell_back(&mut self, attribute_id: AttributeId) {
+        let tmg_id = msg::source();
+        let purchase = *self
+            .purchases
+            .get(&(tmg_id, attribute_id))
+            .expect("Tamagotchi doesn't own that attribute");
+        assert!(
+            exec::block_height().saturating_sub(purchase.block) < self.refund_policy.window,
+            "Refund window has expired"
+        );
+        let refund = purchase.price * Price::from(self.refund_policy.percent) / 100;
+        let token = match purchase.currency {
+            Currency::FungibleToken(token) => token,
+            Currency::Native => {
+                self.sell_back_natively(&tmg_id, attribute_id, refund);
+                return;
+            }
+        };
+
+        // the attribute, the refund and the points earned with it are taken away
+        // before awaiting the transfer so the same purchase can't be refunded twice
+        let reward = self.claw_back_reward(&purchase, &token);
+        self.take_from_treasury(&token, refund);
+        self.remove_owned(&tmg_id, attribute_id);
+        let points = self.spend_points(&purchase.buyer, purchase.points);
+
+        let reason = TransferReason::Refund {
+            attribute_id,
+            purchase,
+            points,
+            referral_reward: reward,
+        };
+        let success = self
+            .transfer_from_store(&token, &tmg_id, refund, reason)
+            .await;
+
+        msg::reply(StoreEvent::AttributeSoldBack { success }, 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeSoldBack`");
+    }
+
+    fn sell_back_natively(
+        &mut self,
+        tmg_id: &TamagotchiId,
+        attribute_id: AttributeId,
+        refund: u128,
+    ) {
+        assert!(
+            refund <= self.native_treasury,
+            "Not enough value in the treasury to refund"
+        );
+        self.remove_owned(tmg_id, attribute_id);
+        self.native_treasury -= refund;
+        self.native_revenue = self.native_revenue.saturating_sub(refund);
+
+        msg::reply(StoreEvent::AttributeSoldBack { success: true }, refund)
+            .expect("Error in sending a reply `StoreEvent::AttributeSoldBack`");
+    }
+
+    fn set_refund_policy(&mut self, percent: u8, window: BlockNumber) {
+        self.check_role(Role::Treasurer, "Only treasurer can set refund policy");
+        assert!(percent <= 100, "Refund percent can't exceed 100");
+        self.refund_policy = RefundPolicy { percent, window };
+        msg::reply(StoreEvent::RefundPolicySet { percent, window }, 0)
+            .expect("Error in sending a reply `StoreEvent::RefundPolicySet`");
+    }
+
+    fn set_native_price(&mut self, attribute_id: AttributeId, price: Option<u128>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set native prices",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        match price {
+            Some(price) => self.native_prices.insert(attribute_id, price),
+            None => self.native_prices.remove(&attribute_id),
+        };
+        msg::reply(StoreEvent::NativePriceSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::NativePriceSet`");
+    }
+
+    fn withdraw_native(&mut self, to: &ActorId, amount: u128) {
+        self.check_role(Role::Treasurer, "Only treasurer can withdraw value");
+        assert!(
+            amount <= self.native_treasury,
+            "Not enough value in the treasury"
+        );
+        self.native_treasury -= amount;
+        msg::send_bytes(*to, [], amount).expect("Error in sending value");
+        msg::reply(StoreEvent::NativeWithdrawn { amount }, 0)
+            .expect("Error in sending a reply `StoreEvent::NativeWithdrawn`");
+    }
+
+    async fn withdraw(&mut self, token: &ActorId, to: &ActorId, amount: Price) {
+        self.check_role(Role::Treasurer, "Only treasurer can withdraw tokens");
+        self.take_from_treasury(token, amount);
+
+        let success = self
+            .transfer_from_store(token, to, amount, TransferReason::Withdrawal)
+            .await;
+
+        msg::reply(StoreEvent::Withdrawn { amount, success }, 0)
+            .expect("Error in sending a reply `StoreEvent::Withdrawn`");
+    }
+
+    fn set_revenue_split(&mut self, attribute_id: AttributeId, beneficiaries: Vec<(ActorId, u8)>) {
+        self.check_role(Role::Treasurer, "Only treasurer can set revenue splits");
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        let total_percent: u32 = beneficiaries
+            .iter()
+            .map(|(_, percent)| u32::from(*percent))
+            .sum();
+        assert!(
+            total_percent <= 100,
+            "Revenue shares can't exceed 100 percent"
+        );
+
+        if beneficiaries.is_empty() {
+            self.revenue_splits.remove(&attribute_id);
+        } else {
+            self.revenue_splits.insert(attribute_id, beneficiaries);
+        }
+        msg::reply(StoreEvent::RevenueSplitSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::RevenueSplitSet`");
+    }
+
+    async fn pay_out(&mut self, beneficiary: &ActorId, token: &ActorId) {
+        self.check_role(Role::Treasurer, "Only treasurer can pay out revenue");
+        self.claim_revenue(beneficiary, token).await;
+    }
+
+    async fn claim_revenue(&mut self, beneficiary: &ActorId, token: &ActorId) {
+        let amount = self
+            .payouts
+            .remove(&(*beneficiary, *token))
+            .expect("No revenue to claim");
+
+        let success = self
+            .transfer_from_store(token, beneficiary, amount, TransferReason::Payout)
+            .await;
+
+        msg::reply(StoreEvent::RevenueClaimed { amount, success }, 0)
+            .expect("Error in sending a reply `StoreEvent::RevenueClaimed`");
+    }
+
+    fn restore_reward(&mut self, purchase: &Purchase, token: &ActorId, reward: Price) {
+        if reward == 0 {
+            return;
+        }
+        let Some(referrer) = self.referred_by.get(&purchase.buyer).copied() else {
+            return;
+        };
+        self.take_from_treasury(token, reward);
+        *self.payouts.entry((referrer, *token)).or_default() += reward;
+        *self.referral_rewards.entry((referrer, *token)).or_default() += reward;
+    }
+
+    fn take_from_treasury(&mut self, token: &ActorId, amount: Price) {
+        let treasury = self.treasury.entry(*token).or_default();
+        assert!(amount <= *treasury, "Not enough tokens in the treasury");
+        *treasury -= amount;
+    }
+
+    // the transfer is recorded until it's settled,
+    // so it can be reconciled if the message runs out of gas while it's awaited
+    async fn transfer_from_store(
+        &mut self,
+        token: &ActorId,
+        to: &ActorId,
+        amount: Price,
+        reason: TransferReason,
+    ) -> bool {
+        let transaction_id = self.next_transaction_id();
+        self.transfers.insert(
+            transaction_id,
+            OutgoingTransfer {
+                transaction_id,
+                token: *token,
+                to: *to,
+                amount,
+                reason,
+            },
+        );
+        self.send_transfer(transaction_id).await
+    }
 
-impl AttributeStore {
-    fn create_attribute(
+    async fn send_transfer(&mut self, transaction_id: TransactionId) -> bool {
+        let transfer = self.transfers[&transaction_id];
+        self.awaiting_transfers.insert(msg::id(), transaction_id);
+        let success = transfer_tokens(
+            transaction_id,
+            &transfer.token,
+            &exec::program_id(),
+            &transfer.to,
+            transfer.amount,
+        )
+        .await
+        .is_ok();
+        self.awaiting_transfers.remove(&msg::id());
+        self.transfers.remove(&transaction_id);
+        self.settle_transfer(&transfer, success);
+        success
+    }
+
+    fn settle_transfer(&mut self, transfer: &OutgoingTransfer, success: bool) {
+        let token = transfer.token;
+        match transfer.reason {
+            TransferReason::Refund { attribute_id, .. } if success => {
+                // both counters go down by the same amount to keep them in sync
+                let revenue = self.revenue.entry((attribute_id, token)).or_default();
+                let refunded = transfer.amount.min(*revenue);
+                *revenue -= refunded;
+                if let Some(total_revenue) = self.total_revenue.get_mut(&token) {
+                    *total_revenue = total_revenue.saturating_sub(refunded);
+                }
+            }
+            TransferReason::Refund {
+                attribute_id,
+                purchase,
+                points,
+                referral_reward,
+            } => {
+                self.add_owned(&transfer.to, attribute_id, Some(purchase));
+                *self.treasury.entry(token).or_default() += transfer.amount;
+                self.restore_points(&purchase.buyer, points);
+                self.restore_reward(&purchase, &token, referral_reward);
+            }
+            _ if success => {}
+            TransferReason::Withdrawal => {
+                *self.treasury.entry(token).or_default() += transfer.amount;
+            }
+            TransferReason::Payout => {
+                *self.payouts.entry((transfer.to, token)).or_default() += transfer.amount;
+            }
+        }
+    }
+
+    async fn reconcile_transfer(&mut self, transaction_id: TransactionId) {
+        let transfer = self
+            .transfers
+            .get(&transaction_id)
+            .expect("Transfer doesn't need reconciliation");
+        let source = msg::source();
+        assert!(
+            source == transfer.to || self.has_role(&source, Role::Operator),
+            "Only the recipient or operator can reconcile transfers"
+        );
+        assert!(
+            !self
+                .awaiting_transfers
+                .values()
+                .any(|awaited| *awaited == transaction_id),
+            "Transfer is in progress"
+        );
+
+        let success = self.send_transfer(transaction_id).await;
+        msg::reply(
+            StoreEvent::TransferReconciled {
+                transaction_id,
+                success,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::TransferReconciled`");
+    }
+
+    fn accept_token(&mut self, token: &ActorId) {
+        self.check_role(Role::Treasurer, "Only treasurer can accept tokens");
+        self.accepted_tokens.insert(*token);
+        msg::reply(StoreEvent::TokenAccepted { token: *token }, 0)
+            .expect("Error in sending a reply `StoreEvent::TokenAccepted`");
+    }
+
+    fn remove_token(&mut self, token: &ActorId) {
+        self.check_role(Role::Treasurer, "Only treasurer can remove tokens");
+        self.accepted_tokens.remove(token);
+        self.token_prices
+            .retain(|(_, price_token), _| price_token != token);
+        msg::reply(StoreEvent::TokenRemoved { token: *token }, 0)
+            .expect("Error in sending a reply `StoreEvent::TokenRemoved`");
+    }
+
+    fn set_token_price(
         &mut self,
         attribute_id: AttributeId,
-        metadata: &AttrMetadata,
-        price: Price,
+        token: &ActorId,
+        price: Option<Price>,
     ) {
-        assert_eq!(msg::source(), self.admin, "Only admin can add attributes");
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set token prices",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        assert!(
+            self.accepted_tokens.contains(token),
+            "The store doesn't accept that token"
+        );
+        match price {
+            Some(price) => self.token_prices.insert((attribute_id, *token), price),
+            None => self.token_prices.remove(&(attribute_id, *token)),
+        };
+        msg::reply(StoreEvent::TokenPriceSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::TokenPriceSet`");
+    }
 
-        if self
-            .attributes
-            .insert(attribute_id, (metadata.clone(), price))
-            .is_some()
-        {
-            panic!("Attribute with that ID already exists");
+    fn grant_role(&mut self, account: &ActorId, role: Role) {
+        self.check_role(Role::Owner, "Only owner can grant roles");
+        self.roles.entry(*account).or_default().insert(role);
+        msg::reply(
+            StoreEvent::RoleGranted {
+                account: *account,
+                role,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::RoleGranted`");
+    }
+
+    fn revoke_role(&mut self, account: &ActorId, role: Role) {
+        self.check_role(Role::Owner, "Only owner can revoke roles");
+        if let Some(roles) = self.roles.get_mut(account) {
+            roles.remove(&role);
+            if roles.is_empty() {
+                self.roles.remove(account);
+            }
         }
+        msg::reply(
+            StoreEvent::RoleRevoked {
+                account: *account,
+                role,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::RoleRevoked`");
+    }
 
-        msg::reply(StoreEvent::AttributeCreated { attribute_id }, 0)
-            .expect("Error in sending a reply `StoreEvent::AttributeCreated");
+    fn transfer_admin(&mut self, new_admin: &ActorId) {
+        assert_eq!(
+            msg::source(),
+            self.admin,
+            "Only admin can transfer admin rights"
+        );
+        self.admin = *new_admin;
+        msg::reply(
+            StoreEvent::AdminTransferred {
+                new_admin: *new_admin,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AdminTransferred`");
     }
-    async fn buy_attribute(&mut self, attribute_id: AttributeId) {
-        let (transaction_id, attribute_id) = if let Some((transaction_id, prev_attribute_id)) =
-            self.transactions.get(&msg::source())
-        {
-            // if `prev_attribute_id` is not equal to `attribute_id` then it means that transaction didn`t completed
-            // we ask the tamagotchi contract to complete the previous transaction
-            if attribute_id != *prev_attribute_id {
-                msg::reply(
-                    StoreEvent::CompletePrevTx {
-                        attribute_id: *prev_attribute_id,
-                    },
-                    0,
-                )
-                .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
-                return;
-            }
-            (*transaction_id, *prev_attribute_id)
+
+    fn set_paused(&mut self, paused: bool) {
+        self.check_role(Role::Operator, "Only operator can pause the store");
+        self.paused = paused;
+        let event = if paused {
+            StoreEvent::Paused
         } else {
-            let current_transaction_id = self.transaction_id;
-            self.transaction_id = self.transaction_id.wrapping_add(1);
-            self.transactions
-                .insert(msg::source(), (current_transaction_id, attribute_id));
-            (current_transaction_id, attribute_id)
+            StoreEvent::Unpaused
         };
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Paused`");
+    }
 
-        let result = self.sell_attribute(transaction_id, attribute_id).await;
-        self.transactions.remove(&msg::source());
+    fn has_role(&self, account: &ActorId, role: Role) -> bool {
+        *account == self.admin
+            || self
+                .roles
+                .get(account)
+                .is_some_and(|roles| roles.contains(&Role::Owner) || roles.contains(&role))
+    }
 
-        msg::reply(StoreEvent::AttributeSold { success: result }, 0)
-            .expect("Error in sending a reply `StoreEvent::AttributeSold`");
+    fn check_role(&self, role: Role, error: &str) {
+        assert!(self.has_role(&msg::source(), role), "{error}");
+    }
+
+    fn transfer_attribute(&mut self, attribute_id: AttributeId, to: &TamagotchiId) {
+        let from = msg::source();
+        self.take_owned(&from, attribute_id);
+        self.give_owned(to, attribute_id);
+        msg::reply(
+            StoreEvent::AttributeTransferred {
+                attribute_id,
+                to: *to,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AttributeTransferred`");
     }
 
-    async fn sell_attribute(
+    fn offer_trade(&mut self, give: AttributeId, want: AttributeId, counterparty: &TamagotchiId) {
+        let offerer = msg::source();
+        assert_ne!(offerer, *counterparty, "Can't trade with yourself");
+        // the offered attribute is held by the store until the trade is settled
+        self.take_owned(&offerer, give);
+
+        let trade_id = self.trade_id;
+        self.trade_id = self.trade_id.wrapping_add(1);
+        self.trades.insert(
+            trade_id,
+            Trade {
+                offerer,
+                give,
+                want,
+                counterparty: *counterparty,
+            },
+        );
+        msg::reply(StoreEvent::TradeOffered { trade_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::TradeOffered`");
+    }
+
+    fn accept_trade(&mut self, trade_id: TradeId) {
+        let trade = self.trades.remove(&trade_id).expect("Trade doesn't exist");
+        assert_eq!(
+            msg::source(),
+            trade.counterparty,
+            "Only the counterparty can accept the trade"
+        );
+        self.take_owned(&trade.counterparty, trade.want);
+        self.give_owned(&trade.offerer, trade.want);
+        self.give_owned(&trade.counterparty, trade.give);
+        msg::reply(StoreEvent::TradeAccepted { trade_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::TradeAccepted`");
+    }
+
+    fn cancel_trade(&mut self, trade_id: TradeId) {
+        let trade = self.trades.remove(&trade_id).expect("Trade doesn't exist");
+        assert_eq!(
+            msg::source(),
+            trade.offerer,
+            "Only the offerer can cancel the trade"
+        );
+        self.give_owned(&trade.offerer, trade.give);
+        msg::reply(StoreEvent::TradeCancelled { trade_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::TradeCancelled`");
+    }
+
+    // moves an owned attribute out of the tamagotchi's inventory,
+    // it can't be sold back after that
+    fn take_owned(&mut self, tmg_id: &TamagotchiId, attribute_id: AttributeId) {
+        assert!(
+            self.owns(tmg_id, attribute_id),
+            "Tamagotchi doesn't own that attribute"
+        );
+        self.remove_owned(tmg_id, attribute_id);
+    }
+
+    // consumables stack and mystery boxes are opened, so only other attributes can't be owned twice
+    fn check_can_receive(&self, tmg_id: &TamagotchiId, attribute_id: AttributeId) {
+        if let Some(pool) = self.mystery_boxes.get(&attribute_id) {
+            assert!(
+                !self.drawable(tmg_id, pool).is_empty(),
+                "Tamagotchi already owns everything in the mystery box"
+            );
+            return;
+        }
+        assert!(
+            self.consumables.contains_key(&attribute_id) || !self.holds(tmg_id, attribute_id),
+            "Tamagotchi already owns that attribute"
+        );
+    }
+
+    fn give_owned(&mut self, tmg_id: &TamagotchiId, attribute_id: AttributeId) {
+        assert!(
+            !self.holds(tmg_id, attribute_id),
+            "Tamagotchi already owns that attribute"
+        );
+        self.add_owned(tmg_id, attribute_id, None);
+    }
+
+    // an attribute offered in an open trade still belongs to the offerer,
+    // it's given back if the trade is cancelled
+    fn holds(&self, tmg_id: &TamagotchiId, attribute_id: AttributeId) -> bool {
+        self.owns(tmg_id, attribute_id)
+            || self
+                .trades
+                .values()
+                .any(|trade| trade.offerer == *tmg_id && trade.give == attribute_id)
+    }
+
+    fn owns(&self, tmg_id: &TamagotchiId, attribute_id: AttributeId) -> bool {
+        self.owners
+            .get(tmg_id)
+            .is_some_and(|attributes| attributes.contains(&attribute_id))
+    }
+
+    fn add_owned(
         &mut self,
-        transaction_id: TransactionId,
+        tmg_id: &TamagotchiId,
         attribute_id: AttributeId,
-    ) -> bool {
-        let (_, price) = self
-            .attributes
-            .get(&attribute_id)
-            .expect("Can`t get attribute_id");
+        purchase: Option<Purchase>,
+    ) {
+        self.owners
+            .entry(*tmg_id)
+            .and_modify(|attributes| {
+                attributes.insert(attribute_id);
+            })
+            .or_insert_with(|| [attribute_id].into());
+        if let Some(purchase) = purchase {
+            self.purchases.insert((*tmg_id, attribute_id), purchase);
+        }
+    }
 
-        if transfer_tokens(
-            transaction_id,
-            &self.ft_contract_id,
-            &msg::source(),
-            &exec::program_id(),
-            *price,
-        )
-        .await
-        .is_ok()
-        {
-            self.owners
-                .entry(msg::source())
-                .and_modify(|attributes| {
-                    attributes.insert(attribute_id);
-                })
-                .or_insert_with(|| [attribute_id].into());
-            return true;
+    fn remove_owned(&mut self, tmg_id: &TamagotchiId, attribute_id: AttributeId) {
+        if let Some(attributes) = self.owners.get_mut(tmg_id) {
+            attributes.remove(&attribute_id);
         }
-        false
+        self.purchases.remove(&(*tmg_id, attribute_id));
+    }
+
+    fn next_transaction_id(&mut self) -> TransactionId {
+        let transaction_id = self.transaction_id;
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+        transaction_id
     }
 
     fn get_attributes(&self, tmg_id: &TamagotchiId) {
@@ -112,11 +1806,286 @@ impl AttributeStore {
             .expect("Error in sending a reply `StoreEvent::Attributes`");
     }
 
+    fn set_effects(&mut self, attribute_id: AttributeId, effects: Effects) {
+        self.check_role(Role::CatalogManager, "Only catalog manager can set effects");
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        assert!(
+            effects.decay_reduction <= 100,
+            "Decay reduction can't exceed 100 percent"
+        );
+        self.effects.insert(attribute_id, effects);
+        msg::reply(StoreEvent::EffectsSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::EffectsSet`");
+    }
+
+    fn get_effects(&self, tmg_id: &TamagotchiId) {
+        let effects = self
+            .owners
+            .get(tmg_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|attribute_id| self.effects.get(attribute_id))
+            .fold(Effects::default(), |total, effects| Effects {
+                power: total.power.saturating_add(effects.power),
+                energy: total.energy.saturating_add(effects.energy),
+                decay_reduction: total
+                    .decay_reduction
+                    .saturating_add(effects.decay_reduction)
+                    .min(100),
+            });
+        msg::reply(StoreEvent::Effects { effects }, 0)
+            .expect("Error in sending a reply `StoreEvent::Effects`");
+    }
+
+    fn set_consumable(&mut self, attribute_id: AttributeId, consumable: Option<Consumable>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set consumables",
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        self.check_not_pending(attribute_id);
+        // consumables are counted in `inventory` and other attributes in `owners`,
+        // so the kind can't change while somebody holds the attribute
+        if self.consumables.contains_key(&attribute_id) != consumable.is_some() {
+            assert!(
+                !self.inventory.keys().any(|(_, item)| *item == attribute_id)
+                    && !self
+                        .owners
+                        .values()
+                        .any(|attributes| attributes.contains(&attribute_id))
+                    && !self.trades.values().any(|trade| trade.give == attribute_id),
+                "Attribute is already held by tamagotchis"
+            );
+        }
+        match consumable {
+            Some(consumable) => self.consumables.insert(attribute_id, consumable),
+            None => self.consumables.remove(&attribute_id),
+        };
+        msg::reply(StoreEvent::ConsumableSet { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::ConsumableSet`");
+    }
+
+    fn consume_item(&mut self, item: AttributeId) {
+        let tmg_id = msg::source();
+        let consumable = *self
+            .consumables
+            .get(&item)
+            .expect("Attribute isn't a consumable");
+        let quantity = self
+            .inventory
+            .get_mut(&(tmg_id, item))
+            .expect("Tamagotchi doesn't have that item");
+        *quantity -= 1;
+        if *quantity == 0 {
+            self.inventory.remove(&(tmg_id, item));
+        }
+        msg::reply(StoreEvent::ItemConsumed { item, consumable }, 0)
+            .expect("Error in sending a reply `StoreEvent::ItemConsumed`");
+    }
+
+    async fn buy_mystery_box(&mut self, box_id: AttributeId) {
+        assert!(
+            self.mystery_boxes.contains_key(&box_id),
+            "Attribute isn't a mystery box"
+        );
+        self.buy_attribute(box_id, None, None, 0, None, &msg::source())
+            .await;
+    }
+
+    fn set_mystery_box(&mut self, box_id: AttributeId, pool: Vec<(AttributeId, u32)>) {
+        self.check_role(
+            Role::CatalogManager,
+            "Only catalog manager can set mystery boxes",
+        );
+        assert!(
+            self.attributes.contains_key(&box_id),
+            "Can`t get attribute_id"
+        );
+        self.check_not_pending(box_id);
+        assert!(
+            !self
+                .mystery_boxes
+                .values()
+                .flatten()
+                .any(|(attribute_id, _)| *attribute_id == box_id),
+            "Attribute is already in a mystery box"
+        );
+        for (attribute_id, weight) in &pool {
+            assert!(
+                self.attributes.contains_key(attribute_id)
+                    && !self.mystery_boxes.contains_key(attribute_id)
+                    && *attribute_id != box_id,
+                "Mystery box can only contain regular attributes"
+            );
+            assert!(
+                *weight > 0,
+                "Weight of an attribute in the box must be positive"
+            );
+        }
+
+        if pool.is_empty() {
+            self.mystery_boxes.remove(&box_id);
+        } else {
+            self.mystery_boxes.insert(box_id, pool);
+        }
+        msg::reply(StoreEvent::MysteryBoxSet { box_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::MysteryBoxSet`");
+    }
+
+    fn set_recipe(&mut self, recipe_id: RecipeId, recipe: Option<Recipe>) {
+        self.check_role(Role::CatalogManager, "Only catalog manager can set recipes");
+        match recipe {
+            Some(recipe) => {
+                assert!(
+                    !recipe.ingredients.is_empty(),
+                    "Recipe must have ingredients"
+                );
+                let unique: BTreeSet<_> = recipe.ingredients.iter().collect();
+                assert_eq!(
+                    unique.len(),
+                    recipe.ingredients.len(),
+                    "Recipe ingredients must be unique"
+                );
+                assert!(
+                    recipe
+                        .ingredients
+                        .iter()
+                        .chain([&recipe.result])
+                        .all(|attribute_id| self.attributes.contains_key(attribute_id)),
+                    "Can`t get attribute_id"
+                );
+                self.recipes.insert(recipe_id, recipe);
+            }
+            None => {
+                self.recipes.remove(&recipe_id);
+            }
+        }
+        msg::reply(StoreEvent::RecipeSet { recipe_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::RecipeSet`");
+    }
+
+    async fn craft(&mut self, recipe_id: RecipeId) {
+        let tmg_id = msg::source();
+        if let Some(craft) = self.crafts.get(&tmg_id) {
+            assert_eq!(
+                craft.recipe_id, recipe_id,
+                "Tamagotchi has an unfinished craft"
+            );
+        } else {
+            let recipe = self
+                .recipes
+                .get(&recipe_id)
+                .expect("Recipe doesn't exist")
+                .clone();
+            self.check_can_receive(&tmg_id, recipe.result);
+
+            // the ingredients are held by the store until the fee is settled,
+            // so they can't be traded or used in another craft in the meantime
+            let purchases = recipe
+                .ingredients
+                .iter()
+                .map(|attribute_id| self.purchases.get(&(tmg_id, *attribute_id)).copied())
+                .collect();
+            for attribute_id in &recipe.ingredients {
+                self.take_owned(&tmg_id, *attribute_id);
+            }
+            let transaction_id = self.next_transaction_id();
+            self.crafts.insert(
+                tmg_id,
+                PendingCraft {
+                    transaction_id,
+                    recipe_id,
+                    recipe,
+                    token: self.ft_contract_id,
+                    purchases,
+                },
+            );
+        }
+
+        self.complete_craft(&tmg_id).await;
+    }
+
+    // like purchases, the craft keeps its transaction id between attempts,
+    // so the fee isn't charged twice
+    async fn complete_craft(&mut self, tmg_id: &TamagotchiId) {
+        assert!(
+            self.locked.insert(*tmg_id),
+            "Tamagotchi has a purchase in progress"
+        );
+        self.check_invariants();
+        let craft = self.crafts[tmg_id].clone();
+        let recipe = &craft.recipe;
+        self.awaiting
+            .insert(msg::id(), (*tmg_id, craft.transaction_id));
+        let paid = recipe.fee == 0
+            || transfer_tokens(
+                craft.transaction_id,
+                &craft.token,
+                tmg_id,
+                &exec::program_id(),
+                recipe.fee,
+            )
+            .await
+            .is_ok();
+        self.awaiting.remove(&msg::id());
+        self.locked.remove(tmg_id);
+        self.crafts.remove(tmg_id);
+
+        let delivered = if paid {
+            self.deliver(tmg_id, tmg_id, recipe.result, None, craft.transaction_id)
+        } else {
+            None
+        };
+        let Some(attribute_id) = delivered else {
+            for (attribute_id, purchase) in recipe.ingredients.iter().zip(craft.purchases) {
+                self.add_owned(tmg_id, *attribute_id, purchase);
+            }
+            // the result was received another way while the fee was pending
+            if paid && recipe.fee > 0 {
+                *self.payouts.entry((*tmg_id, craft.token)).or_default() += recipe.fee;
+            }
+            msg::reply(
+                StoreEvent::CraftFailed {
+                    recipe_id: craft.recipe_id,
+                },
+                0,
+            )
+            .expect("Error in sending a reply `StoreEvent::CraftFailed`");
+            return;
+        };
+
+        self.customers.insert(*tmg_id);
+        if recipe.fee > 0 {
+            self.record_sale(recipe.result, &craft.token, recipe.fee);
+        }
+        msg::reply(
+            StoreEvent::Crafted {
+                recipe_id: craft.recipe_id,
+                attribute_id,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::Crafted`");
+    }
+
     fn set_ft_contract_id(&mut self, ft_contract_id: &ActorId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set fungible token contract"
+        self.check_role(
+            Role::Treasurer,
+            "Only treasurer can set fungible token contract",
+        );
+        // pending transactions keep their token, but their prices were quoted in the current one
+        assert!(
+            !self
+                .transactions
+                .values()
+                .any(|tx| tx.token == self.ft_contract_id),
+            "There are pending transactions with the current contract"
         );
         self.ft_contract_id = *ft_contract_id;
         msg::reply(
@@ -129,12 +2098,13 @@ impl AttributeStore {
     }
 
     fn remove_tx(&mut self, tmg_id: &TamagotchiId) {
-        assert_eq!(
-            msg::source(),
-            self.admin,
-            "Only admin can set remove transactions"
+        self.check_role(Role::Operator, "Only operator can remove transactions");
+        assert!(
+            !self.locked.contains(tmg_id),
+            "Tamagotchi has a purchase in progress"
         );
-        self.transactions.remove(tmg_id);
+        self.drop_tx(tmg_id);
+        self.reconciliations.remove(tmg_id);
         msg::reply(
             StoreEvent::TxRemoved {
                 tamagotchi_id: *tmg_id,
@@ -145,37 +2115,286 @@ impl AttributeStore {
     }
 }
 
-#[gstd::async_main]
+#[gstd::async_main(handle_signal = store_handle_signal)]
 async fn main() {
     let action: StoreAction = msg::load().expect("Unable to decode `StoreAction");
     let store: &mut AttributeStore =
         unsafe { STORE.as_mut().expect("The contract is not initialized") };
+
+    // only actions that move tokens are blocked, reads and cleanup keep working
+    if store.paused
+        && matches!(
+            action,
+            StoreAction::BuyAttribute { .. }
+                | StoreAction::GiftAttribute { .. }
+                | StoreAction::BuyAttributeWith { .. }
+                | StoreAction::BuyMysteryBox { .. }
+                | StoreAction::Craft { .. }
+                | StoreAction::RetryTx { .. }
+                | StoreAction::ReconcileTx { .. }
+                | StoreAction::SellBack { .. }
+                | StoreAction::Withdraw { .. }
+                | StoreAction::ClaimRevenue { .. }
+                | StoreAction::WithdrawNative { .. }
+                | StoreAction::ReconcileTransfer { .. }
+                | StoreAction::PayOut { .. }
+        )
+    {
+        msg::reply(StoreEvent::Error(StoreError::Paused), msg::value())
+            .expect("Error in sending a reply `StoreEvent::Error`");
+        return;
+    }
+
+    // only reads, finishing pending transactions, crafts and transfers, emptying the treasury
+    // and the migration itself work while frozen
+    let pending_craft =
+        matches!(action, StoreAction::Craft { .. }) && store.crafts.contains_key(&msg::source());
+    if store.frozen
+        && !pending_craft
+        && !matches!(
+            action,
+            StoreAction::GetAttributes { .. }
+                | StoreAction::GetEffects { .. }
+                | StoreAction::RetryTx { .. }
+                | StoreAction::ReconcileTx { .. }
+                | StoreAction::ReconcileTransfer { .. }
+                | StoreAction::Withdraw { .. }
+                | StoreAction::WithdrawNative { .. }
+                | StoreAction::ClaimRevenue { .. }
+                | StoreAction::PayOut { .. }
+                | StoreAction::ExpireTx { .. }
+                | StoreAction::RemoveTx { .. }
+                | StoreAction::Unfreeze
+                | StoreAction::ExportState { .. }
+                | StoreAction::ImportState { .. }
+        )
+    {
+        msg::reply(StoreEvent::Error(StoreError::Frozen), msg::value())
+            .expect("Error in sending a reply `StoreEvent::Error`");
+        return;
+    }
+
+    if matches!(
+        action,
+        StoreAction::BuyAttribute { .. }
+            | StoreAction::GiftAttribute { .. }
+            | StoreAction::BuyAttributeWith { .. }
+            | StoreAction::BuyMysteryBox { .. }
+            | StoreAction::RetryTx { .. }
+            | StoreAction::ReconcileTx { .. }
+            | StoreAction::Craft { .. }
+            | StoreAction::SellBack { .. }
+            | StoreAction::Withdraw { .. }
+            | StoreAction::ClaimRevenue { .. }
+            | StoreAction::ReconcileTransfer { .. }
+            | StoreAction::PayOut { .. }
+    ) {
+        exec::system_reserve_gas(SIGNAL_GAS).expect("Unable to reserve gas for the signal");
+    }
+
     match action {
         StoreAction::CreateAttribute {
             attribute_id,
             attr_metadata,
             price,
         } => store.create_attribute(attribute_id, &attr_metadata, price),
-        StoreAction::BuyAttribute { attribute_id } => store.buy_attribute(attribute_id).await,
+        StoreAction::BuyAttribute { attribute_id } => {
+            store
+                .buy_attribute(attribute_id, None, None, 0, None, &msg::source())
+                .await
+        }
+        StoreAction::GiftAttribute {
+            attribute_id,
+            recipient,
+        } => {
+            store
+                .buy_attribute(attribute_id, None, None, 0, None, &recipient)
+                .await
+        }
         StoreAction::GetAttributes { tamagotchi_id } => store.get_attributes(&tamagotchi_id),
         StoreAction::SetFtContractId { ft_contract_id } => {
             store.set_ft_contract_id(&ft_contract_id)
         }
         StoreAction::RemoveTx { tamagotchi_id } => store.remove_tx(&tamagotchi_id),
+        StoreAction::SellBack { attribute_id } => store.sell_back(attribute_id).await,
+        StoreAction::SetRefundPolicy { percent, window } => {
+            store.set_refund_policy(percent, window)
+        }
+        StoreAction::Withdraw { token, to, amount } => store.withdraw(&token, &to, amount).await,
+        StoreAction::SetRevenueSplit {
+            attribute_id,
+            beneficiaries,
+        } => store.set_revenue_split(attribute_id, beneficiaries),
+        StoreAction::ClaimRevenue { token } => store.claim_revenue(&msg::source(), &token).await,
+        StoreAction::GrantRole { account, role } => store.grant_role(&account, role),
+        StoreAction::RevokeRole { account, role } => store.revoke_role(&account, role),
+        StoreAction::TransferAdmin { new_admin } => store.transfer_admin(&new_admin),
+        StoreAction::Pause => store.set_paused(true),
+        StoreAction::Unpause => store.set_paused(false),
+        StoreAction::RetryTx { tamagotchi_id } => store.retry_tx(&tamagotchi_id).await,
+        StoreAction::ExpireTx {
+            tamagotchi_id,
+            transaction_id,
+        } => store.expire_tx(&tamagotchi_id, transaction_id),
+        StoreAction::SetTxTimeout { timeout } => store.set_tx_timeout(timeout),
+        StoreAction::SetNativePrice {
+            attribute_id,
+            price,
+        } => store.set_native_price(attribute_id, price),
+        StoreAction::WithdrawNative { to, amount } => store.withdraw_native(&to, amount),
+        StoreAction::AcceptToken { token } => store.accept_token(&token),
+        StoreAction::RemoveToken { token } => store.remove_token(&token),
+        StoreAction::SetTokenPrice {
+            attribute_id,
+            token,
+            price,
+        } => store.set_token_price(attribute_id, &token, price),
+        StoreAction::CreateCoupon {
+            code,
+            discount,
+            max_uses,
+            expires_at,
+            attribute_id,
+        } => store.create_coupon(code, discount, max_uses, expires_at, attribute_id),
+        StoreAction::RemoveCoupon { code } => store.remove_coupon(code),
+        StoreAction::SetAvailability {
+            attribute_id,
+            available_from,
+            available_until,
+        } => store.set_availability(attribute_id, available_from, available_until),
+        StoreAction::ScheduleSale { attribute_id, sale } => store.schedule_sale(attribute_id, sale),
+        StoreAction::ClearSales { attribute_id } => store.clear_sales(attribute_id),
+        StoreAction::SetBondingCurve {
+            attribute_id,
+            growth,
+        } => store.set_bonding_curve(attribute_id, growth),
+        StoreAction::TransferAttribute { attribute_id, to } => {
+            store.transfer_attribute(attribute_id, &to)
+        }
+        StoreAction::OfferTrade {
+            give,
+            want,
+            counterparty,
+        } => store.offer_trade(give, want, &counterparty),
+        StoreAction::AcceptTrade { trade_id } => store.accept_trade(trade_id),
+        StoreAction::CancelTrade { trade_id } => store.cancel_trade(trade_id),
+        StoreAction::SetEffects {
+            attribute_id,
+            effects,
+        } => store.set_effects(attribute_id, effects),
+        StoreAction::GetEffects { tamagotchi_id } => store.get_effects(&tamagotchi_id),
+        StoreAction::SetConsumable {
+            attribute_id,
+            consumable,
+        } => store.set_consumable(attribute_id, consumable),
+        StoreAction::ConsumeItem { item } => store.consume_item(item),
+        StoreAction::SetMysteryBox { box_id, pool } => store.set_mystery_box(box_id, pool),
+        StoreAction::BuyMysteryBox { box_id } => store.buy_mystery_box(box_id).await,
+        StoreAction::SetRecipe { recipe_id, recipe } => store.set_recipe(recipe_id, recipe),
+        StoreAction::Craft { recipe_id } => store.craft(recipe_id).await,
+        StoreAction::SetPresale {
+            attribute_id,
+            presale,
+        } => store.set_presale(attribute_id, presale),
+        StoreAction::AddToWhitelist {
+            attribute_id,
+            tamagotchis,
+        } => store.update_whitelist(attribute_id, tamagotchis, true),
+        StoreAction::RemoveFromWhitelist {
+            attribute_id,
+            tamagotchis,
+        } => store.update_whitelist(attribute_id, tamagotchis, false),
+        StoreAction::SetLoyaltyPolicy { percent, expiry } => {
+            store.set_loyalty_policy(percent, expiry)
+        }
+        StoreAction::SetPointsPrice {
+            attribute_id,
+            points,
+        } => store.set_points_price(attribute_id, points),
+        StoreAction::RedeemPoints { attribute_id } => store.redeem_points(attribute_id),
+        StoreAction::SetReferralReward { percent } => store.set_referral_reward(percent),
+        StoreAction::ReconcileTx { tamagotchi_id } => store.reconcile_tx(&tamagotchi_id).await,
+        StoreAction::Freeze => store.set_frozen(true),
+        StoreAction::Unfreeze => store.set_frozen(false),
+        StoreAction::ExportState { index } => store.export_state(index),
+        StoreAction::ImportState { chunk } => store.import_state(chunk),
+        StoreAction::BuyAttributeWith {
+            attribute_id,
+            pay_with,
+            coupon,
+            points,
+            referrer,
+        } => {
+            store
+                .buy_attribute(
+                    attribute_id,
+                    pay_with,
+                    coupon,
+                    points,
+                    referrer,
+                    &msg::source(),
+                )
+                .await
+        }
+        StoreAction::ReconcileTransfer { transaction_id } => {
+            store.reconcile_transfer(transaction_id).await
+        }
+        StoreAction::PayOut { beneficiary, token } => store.pay_out(&beneficiary, &token).await,
     }
 }
 
+fn store_handle_signal() {
+    let store = unsafe { STORE.as_mut().expect("The contract is not initialized") };
+    let message_id = msg::signal_from().expect("Unable to get the signal source");
+    store.mark_for_reconciliation(&message_id);
+    // the transfer stays recorded until it's reconciled
+    store.awaiting_transfers.remove(&message_id);
+}
+
 #[no_mangle]
 extern fn init() {
     let ft_contract_id: ActorId = msg::load().expect("Unable to decode `ActorId`");
     let store = AttributeStore {
         admin: msg::source(),
         ft_contract_id,
+        tx_timeout: DEFAULT_TX_TIMEOUT,
         ..Default::default()
     };
     unsafe { STORE = Some(store) };
 }
 
+// picks an attribute from the pool with a probability proportional to its weight
+fn draw(pool: &[(AttributeId, u32)], transaction_id: TransactionId) -> AttributeId {
+    let mut subject = [0; 32];
+    subject[..8].copy_from_slice(&transaction_id.to_le_bytes());
+    let (random, _) = exec::random(subject).expect("Error in getting a random value");
+
+    let total_weight: u64 = pool.iter().map(|(_, weight)| u64::from(*weight)).sum();
+    let mut point =
+        u64::from_le_bytes(random[..8].try_into().expect("Slice has 8 bytes")) % total_weight;
+    for (attribute_id, weight) in pool {
+        let weight = u64::from(*weight);
+        if point < weight {
+            return *attribute_id;
+        }
+        point -= weight;
+    }
+    unreachable!("The point is always less than the total weight")
+}
+
+fn apply_discount(price: Price, discount: Discount) -> Price {
+    match discount {
+        Discount::Percent(percent) => price - price * Price::from(percent) / 100,
+        Discount::Fixed(amount) => price.saturating_sub(amount),
+    }
+}
+
+fn check_discount(discount: Discount) {
+    if let Discount::Percent(percent) = discount {
+        assert!(percent <= 100, "Discount percent can't exceed 100");
+    }
+}
+
 async fn transfer_tokens(
     transaction_id: TransactionId,
     token_address: &ActorId,
@@ -209,16 +2428,5 @@ async fn transfer_tokens(
 extern fn state() {
     let store = unsafe { STORE.as_ref().expect("The contract is not initialized") };
 
-    msg::reply(
-        store_io::AttributeStore {
-            admin: store.admin,
-            ft_contract_id: store.ft_contract_id,
-            attributes: store.attributes.clone(),
-            owners: store.owners.clone(),
-            transaction_id: store.transaction_id,
-            transactions: store.transactions.clone(),
-        },
-        0,
-    )
-    .expect("Failed to share state");
+    msg::reply(store.snapshot(), 0).expect("Failed to share state");
 }
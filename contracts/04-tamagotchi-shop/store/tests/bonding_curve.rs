@@ -0,0 +1,38 @@
+use gstd::prelude::*;
+use gtest::System;
+use store_io::{CurveGrowth, StoreAction};
+
+mod common;
+use common::*;
+
+#[test]
+fn every_sale_raises_the_price() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+
+    admin_send(
+        &store,
+        StoreAction::SetBondingCurve {
+            attribute_id: SKIN,
+            growth: Some(CurveGrowth::Linear { percent: 10 }),
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).contains(&sold(OTHER_TAMAGOTCHI, true)));
+    assert_eq!(
+        balance(&balances, OTHER_TAMAGOTCHI),
+        BALANCE - PRICE * 110 / 100
+    );
+
+    // removing the curve fixes the price again
+    admin_send(
+        &store,
+        StoreAction::SetBondingCurve {
+            attribute_id: SKIN,
+            growth: None,
+        },
+    );
+    assert!(state(&store).bonding_curves.is_empty());
+}
@@ -0,0 +1,204 @@
+// shared by all the test binaries, each of them only uses a part of it
+#![allow(dead_code)]
+
+use gstd::{collections::BTreeMap, prelude::*, ActorId};
+use gtest::{Program, RunResult, System, WasmProgram};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use std::{cell::RefCell, rc::Rc};
+use store_io::{AttrMetadata, AttributeId, AttributeStore, StoreAction, StoreEvent};
+
+pub const ADMIN: u64 = 10;
+pub const TAMAGOTCHI: u64 = 20;
+pub const OTHER_TAMAGOTCHI: u64 = 21;
+pub const POOR_TAMAGOTCHI: u64 = 22;
+pub const BENEFICIARY: u64 = 30;
+pub const FT_CONTRACT: u64 = 100;
+pub const NEW_FT_CONTRACT: u64 = 101;
+
+pub const SKIN: AttributeId = 1;
+pub const HAT: AttributeId = 2;
+pub const PRICE: u128 = 1_000;
+pub const BALANCE: u128 = 10 * PRICE;
+
+pub type Balances = Rc<RefCell<BTreeMap<ActorId, u128>>>;
+
+// moves tokens between the shared balances and replies like the sharded FT,
+// a transaction id that was already used gets the same reply again
+#[derive(Debug, Default)]
+pub struct Ft {
+    balances: Balances,
+    transactions: BTreeMap<u64, bool>,
+}
+
+impl Ft {
+    fn transfer(&mut self, sender: ActorId, recipient: ActorId, amount: u128) -> bool {
+        let mut balances = self.balances.borrow_mut();
+        let balance = balances.entry(sender).or_default();
+        if *balance < amount {
+            return false;
+        }
+        *balance -= amount;
+        *balances.entry(recipient).or_default() += amount;
+        true
+    }
+}
+
+impl WasmProgram for Ft {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        let FTokenAction::Message {
+            transaction_id,
+            payload:
+                LogicAction::Transfer {
+                    sender,
+                    recipient,
+                    amount,
+                },
+        } = FTokenAction::decode(&mut &payload[..]).map_err(|_| "Unable to decode the action")?
+        else {
+            return Err("Only transfers are supported");
+        };
+
+        let success = match self.transactions.get(&transaction_id) {
+            Some(success) => *success,
+            None => {
+                let success = self.transfer(sender, recipient, amount);
+                self.transactions.insert(transaction_id, success);
+                success
+            }
+        };
+        let event = if success {
+            FTokenEvent::Ok
+        } else {
+            FTokenEvent::Err
+        };
+        Ok(Some(event.encode()))
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
+    }
+}
+
+// never replies to transfers, so purchases stay in flight for the rest of the test
+#[derive(Debug)]
+pub struct SilentFt;
+
+impl WasmProgram for SilentFt {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
+    }
+}
+
+// the store with `SKIN` and `HAT` in the catalog and the tamagotchis holding `BALANCE` tokens
+pub fn init_store(sys: &System) -> (Program<'_>, Balances) {
+    sys.init_logger();
+    let balances = mock_ft(sys, FT_CONTRACT);
+    (create_store(sys), balances)
+}
+
+pub fn init_store_with<T: WasmProgram + 'static>(sys: &System, ft: T) -> Program<'_> {
+    sys.init_logger();
+    let ft = Program::mock_with_id(sys, FT_CONTRACT, ft);
+    assert!(!ft.send_bytes(ADMIN, []).main_failed());
+    create_store(sys)
+}
+
+// a replying FT contract where the tamagotchis hold `BALANCE` tokens
+pub fn mock_ft(sys: &System, id: u64) -> Balances {
+    let balances = Balances::default();
+    for tamagotchi in [TAMAGOTCHI, OTHER_TAMAGOTCHI] {
+        balances
+            .borrow_mut()
+            .insert(ActorId::from(tamagotchi), BALANCE);
+    }
+    let ft = Ft {
+        balances: balances.clone(),
+        ..Default::default()
+    };
+    let ft = Program::mock_with_id(sys, id, ft);
+    assert!(!ft.send_bytes(ADMIN, []).main_failed());
+    balances
+}
+
+fn create_store(sys: &System) -> Program<'_> {
+    let store = Program::current(sys);
+    assert!(!store.send(ADMIN, ActorId::from(FT_CONTRACT)).main_failed());
+    for attribute_id in [SKIN, HAT] {
+        create_attribute(&store, attribute_id);
+    }
+    store
+}
+
+pub fn create_attribute(store: &Program<'_>, attribute_id: AttributeId) {
+    admin_send(
+        store,
+        StoreAction::CreateAttribute {
+            attribute_id,
+            attr_metadata: metadata(),
+            price: PRICE,
+        },
+    );
+}
+
+pub fn metadata() -> AttrMetadata {
+    AttrMetadata {
+        title: String::from("Attribute"),
+        description: String::from("Attribute description"),
+        media: String::from("https://example.com/attribute.png"),
+    }
+}
+
+// for the setup done by the admin, which must succeed
+pub fn admin_send(store: &Program<'_>, action: StoreAction) -> RunResult {
+    let res = store.send(ADMIN, action);
+    assert!(!res.main_failed());
+    res
+}
+
+pub fn buy(store: &Program<'_>, tamagotchi: u64, attribute_id: AttributeId) -> RunResult {
+    store.send(tamagotchi, StoreAction::BuyAttribute { attribute_id })
+}
+
+pub fn sold(tamagotchi: u64, success: bool) -> (u64, Vec<u8>) {
+    (tamagotchi, StoreEvent::AttributeSold { success }.encode())
+}
+
+pub fn balance(balances: &Balances, account: u64) -> u128 {
+    balances
+        .borrow()
+        .get(&ActorId::from(account))
+        .copied()
+        .unwrap_or_default()
+}
+
+pub fn state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
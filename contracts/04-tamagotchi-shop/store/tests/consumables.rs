@@ -0,0 +1,83 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Consumable, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+const FOOD_PACK: Consumable = Consumable {
+    fed: 1000,
+    entertained: 0,
+    rested: 500,
+};
+
+#[test]
+fn consumables_stack_and_are_used_up() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+    admin_send(
+        &store,
+        StoreAction::SetConsumable {
+            attribute_id: SKIN,
+            consumable: Some(FOOD_PACK),
+        },
+    );
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - 2 * PRICE);
+    let inventory = state(&store).inventory;
+    assert_eq!(inventory[&(tamagotchi, SKIN)], 2);
+    assert!(!state(&store).owners.contains_key(&tamagotchi));
+
+    for left in [1, 0] {
+        let res = store.send(TAMAGOTCHI, StoreAction::ConsumeItem { item: SKIN });
+        assert!(res.contains(&(
+            TAMAGOTCHI,
+            StoreEvent::ItemConsumed {
+                item: SKIN,
+                consumable: FOOD_PACK,
+            }
+            .encode()
+        )));
+        assert_eq!(
+            state(&store)
+                .inventory
+                .get(&(tamagotchi, SKIN))
+                .copied()
+                .unwrap_or_default(),
+            left
+        );
+    }
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::ConsumeItem { item: SKIN })
+        .main_failed());
+}
+
+#[test]
+fn held_attribute_keeps_its_kind() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+    let set_consumable = |attribute_id, consumable| {
+        store.send(
+            ADMIN,
+            StoreAction::SetConsumable {
+                attribute_id,
+                consumable,
+            },
+        )
+    };
+
+    // an owned attribute can't become a consumable
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(set_consumable(SKIN, Some(FOOD_PACK)).main_failed());
+
+    // and a consumable in an inventory can't become a regular attribute
+    assert!(!set_consumable(HAT, Some(FOOD_PACK)).main_failed());
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    assert!(set_consumable(HAT, None).main_failed());
+    // but what it restores can still be changed
+    assert!(!set_consumable(HAT, Some(Consumable::default())).main_failed());
+    assert!(state(&store).consumables.contains_key(&HAT));
+}
@@ -0,0 +1,71 @@
+use gstd::{prelude::*, ActorId};
+use gtest::{Program, RunResult, System};
+use store_io::{AttributeId, Discount, StoreAction};
+
+mod common;
+use common::*;
+
+const CODE: &str = "HALF";
+
+fn buy_with_coupon(store: &Program<'_>, tamagotchi: u64, attribute_id: AttributeId) -> RunResult {
+    store.send(
+        tamagotchi,
+        StoreAction::BuyAttributeWith {
+            attribute_id,
+            pay_with: None,
+            coupon: Some(String::from(CODE)),
+            points: 0,
+            referrer: None,
+        },
+    )
+}
+
+fn create_coupon(store: &Program<'_>, attribute_id: Option<AttributeId>) {
+    admin_send(
+        store,
+        StoreAction::CreateCoupon {
+            code: String::from(CODE),
+            discount: Discount::Percent(50),
+            max_uses: 1,
+            expires_at: 100,
+            attribute_id,
+        },
+    );
+}
+
+#[test]
+fn coupon_uses_are_counted() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    create_coupon(&store, None);
+
+    // a purchase that isn't paid gives the use back
+    let res = buy_with_coupon(&store, POOR_TAMAGOTCHI, SKIN);
+    assert!(res.contains(&sold(POOR_TAMAGOTCHI, false)));
+    assert_eq!(state(&store).coupons[CODE].uses, 0);
+
+    let res = buy_with_coupon(&store, TAMAGOTCHI, SKIN);
+    assert!(res.contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE / 2);
+    assert_eq!(state(&store).coupons[CODE].uses, 1);
+
+    // the only use has been redeemed
+    assert!(buy_with_coupon(&store, OTHER_TAMAGOTCHI, SKIN).main_failed());
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE);
+}
+
+#[test]
+fn coupon_is_limited_to_its_attribute_and_expiry() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    create_coupon(&store, Some(HAT));
+
+    assert!(buy_with_coupon(&store, TAMAGOTCHI, SKIN).main_failed());
+
+    sys.spend_blocks(100);
+    assert!(buy_with_coupon(&store, TAMAGOTCHI, HAT).main_failed());
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE);
+    assert!(!state(&store)
+        .owners
+        .contains_key(&ActorId::from(TAMAGOTCHI)));
+}
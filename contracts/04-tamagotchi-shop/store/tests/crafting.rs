@@ -0,0 +1,80 @@
+use gstd::{collections::BTreeSet, prelude::*, ActorId};
+use gtest::{Program, System};
+use store_io::{AttributeId, Price, Recipe, RecipeId, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+const CROWN: AttributeId = 3;
+const RECIPE: RecipeId = 1;
+
+fn set_recipe(store: &Program<'_>, fee: Price) {
+    create_attribute(store, CROWN);
+    admin_send(
+        store,
+        StoreAction::SetRecipe {
+            recipe_id: RECIPE,
+            recipe: Some(Recipe {
+                ingredients: vec![SKIN, HAT],
+                fee,
+                result: CROWN,
+            }),
+        },
+    );
+}
+
+#[test]
+fn ingredients_are_crafted_into_the_result() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    set_recipe(&store, PRICE / 2);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    // the hat is missing
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::Craft { recipe_id: RECIPE })
+        .main_failed());
+
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    let res = store.send(TAMAGOTCHI, StoreAction::Craft { recipe_id: RECIPE });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::Crafted {
+            recipe_id: RECIPE,
+            attribute_id: CROWN,
+        }
+        .encode()
+    )));
+    assert_eq!(
+        balance(&balances, TAMAGOTCHI),
+        BALANCE - 2 * PRICE - PRICE / 2
+    );
+    let state = state(&store);
+    assert_eq!(
+        state.owners[&ActorId::from(TAMAGOTCHI)],
+        BTreeSet::from([CROWN])
+    );
+    assert!(state.crafts.is_empty());
+}
+
+#[test]
+fn unpaid_craft_gives_the_ingredients_back() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    set_recipe(&store, BALANCE);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    let res = store.send(TAMAGOTCHI, StoreAction::Craft { recipe_id: RECIPE });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::CraftFailed { recipe_id: RECIPE }.encode()
+    )));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - 2 * PRICE);
+    let state = state(&store);
+    assert_eq!(
+        state.owners[&ActorId::from(TAMAGOTCHI)],
+        BTreeSet::from([SKIN, HAT])
+    );
+    assert!(state.crafts.is_empty());
+}
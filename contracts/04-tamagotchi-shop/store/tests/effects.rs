@@ -0,0 +1,79 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Effects, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn owned_effects_are_summed() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+    let get_effects = || {
+        store.send(
+            TAMAGOTCHI,
+            StoreAction::GetEffects {
+                tamagotchi_id: ActorId::from(TAMAGOTCHI),
+            },
+        )
+    };
+
+    admin_send(
+        &store,
+        StoreAction::SetEffects {
+            attribute_id: SKIN,
+            effects: Effects {
+                power: 10,
+                energy: 5,
+                decay_reduction: 60,
+            },
+        },
+    );
+    admin_send(
+        &store,
+        StoreAction::SetEffects {
+            attribute_id: HAT,
+            effects: Effects {
+                power: 3,
+                energy: 0,
+                decay_reduction: 70,
+            },
+        },
+    );
+    // effects can't slow the decay down by more than 100 percent
+    assert!(store
+        .send(
+            ADMIN,
+            StoreAction::SetEffects {
+                attribute_id: HAT,
+                effects: Effects {
+                    power: 0,
+                    energy: 0,
+                    decay_reduction: 101,
+                },
+            },
+        )
+        .main_failed());
+
+    assert!(get_effects().contains(&(
+        TAMAGOTCHI,
+        StoreEvent::Effects {
+            effects: Effects::default()
+        }
+        .encode()
+    )));
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    assert!(get_effects().contains(&(
+        TAMAGOTCHI,
+        StoreEvent::Effects {
+            effects: Effects {
+                power: 13,
+                energy: 5,
+                decay_reduction: 100,
+            }
+        }
+        .encode()
+    )));
+}
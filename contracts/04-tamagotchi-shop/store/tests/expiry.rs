@@ -0,0 +1,59 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::StoreAction;
+
+mod common;
+use common::*;
+
+#[test]
+fn only_the_store_expires_transactions() {
+    let sys = System::new();
+    let store = init_store_with(&sys, SilentFt);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::SetTxTimeout { timeout: 5 })
+        .main_failed());
+    admin_send(&store, StoreAction::SetTxTimeout { timeout: 5 });
+    assert!(!buy(&store, TAMAGOTCHI, SKIN).main_failed());
+    let transaction_id = state(&store).transactions[&tamagotchi].id;
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::ExpireTx {
+            tamagotchi_id: tamagotchi,
+            transaction_id,
+        },
+    );
+    assert!(res.main_failed());
+
+    // the transfer may still be answered, so the scheduled expiry keeps the transaction
+    sys.spend_blocks(10);
+    assert_eq!(state(&store).transactions[&tamagotchi].id, transaction_id);
+
+    // only the buyer or an operator can retry it
+    let res = store.send(
+        OTHER_TAMAGOTCHI,
+        StoreAction::RetryTx {
+            tamagotchi_id: tamagotchi,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn nothing_to_retry() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    // the settled transaction is gone
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::RetryTx {
+            tamagotchi_id: ActorId::from(TAMAGOTCHI),
+        },
+    );
+    assert!(res.main_failed());
+    assert!(state(&store).transactions.is_empty());
+}
@@ -0,0 +1,82 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn gift_goes_to_the_recipient() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let recipient = ActorId::from(OTHER_TAMAGOTCHI);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GiftAttribute {
+            attribute_id: SKIN,
+            recipient,
+        },
+    );
+    assert!(res.contains(&sold(TAMAGOTCHI, true)));
+    assert!(res.contains(&(
+        OTHER_TAMAGOTCHI,
+        StoreEvent::GiftReceived {
+            attribute_id: SKIN,
+            from: ActorId::from(TAMAGOTCHI),
+        }
+        .encode()
+    )));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE);
+
+    let state = state(&store);
+    assert!(state.owners[&recipient].contains(&SKIN));
+    assert!(!state.owners.contains_key(&ActorId::from(TAMAGOTCHI)));
+
+    // the recipient already has it
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GiftAttribute {
+            attribute_id: SKIN,
+            recipient,
+        },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn pending_gift_keeps_its_recipient() {
+    let sys = System::new();
+    let store = init_store_with(&sys, SilentFt);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+    let recipient = ActorId::from(OTHER_TAMAGOTCHI);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GiftAttribute {
+            attribute_id: SKIN,
+            recipient,
+        },
+    );
+    assert!(!res.main_failed());
+
+    // buying the same attribute for itself has to wait for the gift
+    let res = buy(&store, TAMAGOTCHI, SKIN);
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::CompletePrevTx { attribute_id: SKIN }.encode()
+    )));
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GiftAttribute {
+            attribute_id: SKIN,
+            recipient: ActorId::from(BENEFICIARY),
+        },
+    );
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::CompletePrevTx { attribute_id: SKIN }.encode()
+    )));
+    assert_eq!(state(&store).transactions[&tamagotchi].recipient, recipient);
+}
@@ -0,0 +1,135 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Consumable, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn purchase_locks_the_buyer() {
+    let sys = System::new();
+    let store = init_store_with(&sys, SilentFt);
+
+    let res = buy(&store, TAMAGOTCHI, SKIN);
+    assert!(!res.main_failed());
+    for success in [true, false] {
+        assert!(!res.contains(&(TAMAGOTCHI, StoreEvent::AttributeSold { success }.encode())));
+    }
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+    assert!(state(&store).locked.contains(&tamagotchi));
+
+    // the same purchase can't be settled twice at once
+    assert!(buy(&store, TAMAGOTCHI, SKIN).main_failed());
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::RetryTx {
+            tamagotchi_id: tamagotchi,
+        },
+    );
+    assert!(res.main_failed());
+
+    // a different attribute still has to wait for the previous transaction
+    let res = buy(&store, TAMAGOTCHI, HAT);
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::CompletePrevTx { attribute_id: SKIN }.encode()
+    )));
+
+    // the operator can't drop a transaction that may be paid for any moment
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveTx {
+            tamagotchi_id: tamagotchi,
+        },
+    );
+    assert!(res.main_failed());
+
+    // the lock is per buyer
+    assert!(!buy(&store, OTHER_TAMAGOTCHI, SKIN).main_failed());
+    let state = state(&store);
+    assert!(state.locked.contains(&ActorId::from(OTHER_TAMAGOTCHI)));
+    assert_eq!(state.transactions.len(), 2);
+}
+
+#[test]
+fn catalog_changes_keep_in_flight_purchase() {
+    let sys = System::new();
+    let store = init_store_with(&sys, SilentFt);
+
+    assert!(!buy(&store, TAMAGOTCHI, SKIN).main_failed());
+
+    // the attribute can't be replaced or turned into something else
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id: SKIN,
+            attr_metadata: metadata(),
+            price: PRICE * 2,
+        },
+    );
+    assert!(res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetMysteryBox {
+            box_id: SKIN,
+            pool: vec![(HAT, 1)],
+        },
+    );
+    assert!(res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetConsumable {
+            attribute_id: SKIN,
+            consumable: Some(Consumable {
+                fed: 1,
+                entertained: 1,
+                rested: 1,
+            }),
+        },
+    );
+    assert!(res.main_failed());
+
+    // prices in another token don't change the quoted one
+    let new_ft_contract = ActorId::from(NEW_FT_CONTRACT);
+    let res = store.send(
+        ADMIN,
+        StoreAction::AcceptToken {
+            token: new_ft_contract,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetTokenPrice {
+            attribute_id: SKIN,
+            token: new_ft_contract,
+            price: Some(1),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let tx = state(&store).transactions[&ActorId::from(TAMAGOTCHI)].clone();
+    assert_eq!(tx.attribute_id, SKIN);
+    assert_eq!(tx.price, PRICE);
+    assert_eq!(tx.token, ActorId::from(FT_CONTRACT));
+
+    // the main contract can't change until the purchase is settled
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetFtContractId {
+            ft_contract_id: new_ft_contract,
+        },
+    );
+    assert!(res.main_failed());
+    assert_eq!(state(&store).ft_contract_id, ActorId::from(FT_CONTRACT));
+
+    // other attributes can still be changed
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetMysteryBox {
+            box_id: HAT,
+            pool: vec![(SKIN, 1)],
+        },
+    );
+    assert!(!res.main_failed());
+}
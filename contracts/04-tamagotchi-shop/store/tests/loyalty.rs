@@ -0,0 +1,114 @@
+use gstd::{prelude::*, ActorId};
+use gtest::{Program, RunResult, System};
+use store_io::{AttributeId, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+// a tenth of the price
+const EARNED: u128 = PRICE / 10;
+
+fn buy_with_points(
+    store: &Program<'_>,
+    tamagotchi: u64,
+    attribute_id: AttributeId,
+    points: u128,
+) -> RunResult {
+    store.send(
+        tamagotchi,
+        StoreAction::BuyAttributeWith {
+            attribute_id,
+            pay_with: None,
+            coupon: None,
+            points,
+            referrer: None,
+        },
+    )
+}
+
+fn points(store: &Program<'_>, tamagotchi: u64) -> u128 {
+    state(store)
+        .loyalty
+        .get(&ActorId::from(tamagotchi))
+        .map(|account| account.points)
+        .unwrap_or_default()
+}
+
+fn init_loyalty(sys: &System) -> (Program<'_>, Balances) {
+    let (store, balances) = init_store(sys);
+    admin_send(
+        &store,
+        StoreAction::SetLoyaltyPolicy {
+            percent: 10,
+            expiry: 0,
+        },
+    );
+    (store, balances)
+}
+
+#[test]
+fn points_are_earned_and_spent() {
+    let sys = System::new();
+    let (store, balances) = init_loyalty(&sys);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(points(&store, TAMAGOTCHI), EARNED);
+    assert!(buy_with_points(&store, TAMAGOTCHI, HAT, EARNED + 1).main_failed());
+
+    let res = buy_with_points(&store, TAMAGOTCHI, HAT, EARNED);
+    assert!(res.contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - 2 * PRICE + EARNED);
+    // only the paid part of the price earns points
+    assert_eq!(points(&store, TAMAGOTCHI), (PRICE - EARNED) / 10);
+}
+
+#[test]
+fn unpaid_purchase_gives_the_points_back() {
+    let sys = System::new();
+    let (store, balances) = init_loyalty(&sys);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    balances
+        .borrow_mut()
+        .insert(ActorId::from(TAMAGOTCHI), PRICE - EARNED - 1);
+    let res = buy_with_points(&store, TAMAGOTCHI, HAT, EARNED);
+    assert!(res.contains(&sold(TAMAGOTCHI, false)));
+    assert_eq!(points(&store, TAMAGOTCHI), EARNED);
+}
+
+#[test]
+fn sell_back_takes_the_points_back() {
+    let sys = System::new();
+    let (store, _) = init_loyalty(&sys);
+    admin_send(
+        &store,
+        StoreAction::SetRefundPolicy {
+            percent: 50,
+            window: 10,
+        },
+    );
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(points(&store, TAMAGOTCHI), 2 * EARNED);
+    assert_eq!(
+        state(&store).purchases[&(ActorId::from(TAMAGOTCHI), HAT)].points,
+        EARNED
+    );
+
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: HAT });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::AttributeSoldBack { success: true }.encode()
+    )));
+    assert_eq!(points(&store, TAMAGOTCHI), EARNED);
+
+    // points that were spent already can't be taken back
+    assert!(buy_with_points(&store, TAMAGOTCHI, HAT, EARNED).contains(&sold(TAMAGOTCHI, true)));
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: SKIN });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::AttributeSoldBack { success: true }.encode()
+    )));
+    assert_eq!(points(&store, TAMAGOTCHI), 0);
+}
@@ -0,0 +1,257 @@
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    prelude::*,
+    ActorId,
+};
+use gtest::{Program, RunResult, System};
+use store_io::{checksum, v1, AttrMetadata, StateChunk, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+// enough metadata for the state to take several chunks
+const ATTRIBUTES: u32 = 40;
+
+fn fill_catalog(store: &Program<'_>) {
+    for attribute_id in HAT + 1..=HAT + ATTRIBUTES {
+        admin_send(
+            store,
+            StoreAction::CreateAttribute {
+                attribute_id,
+                attr_metadata: AttrMetadata {
+                    title: format!("Attribute {attribute_id}"),
+                    description: "A very long attribute description. ".repeat(30),
+                    media: String::from("https://example.com/attribute.png"),
+                },
+                price: PRICE,
+            },
+        );
+    }
+}
+
+// the store the state is migrated to
+fn empty_store(sys: &System) -> Program<'_> {
+    let store = Program::current(sys);
+    assert!(!store.send(ADMIN, ActorId::from(FT_CONTRACT)).main_failed());
+    store
+}
+
+fn freeze(store: &Program<'_>) {
+    let res = store.send(ADMIN, StoreAction::Freeze);
+    assert!(res.contains(&(ADMIN, StoreEvent::Frozen.encode())));
+}
+
+fn exported_chunk(res: &RunResult) -> StateChunk {
+    res.log()
+        .iter()
+        .find_map(|log| match StoreEvent::decode(&mut log.payload()) {
+            Ok(StoreEvent::StateExported { chunk }) => Some(chunk),
+            _ => None,
+        })
+        .expect("No exported chunk in the reply")
+}
+
+fn export(store: &Program<'_>) -> Vec<StateChunk> {
+    let first = exported_chunk(&store.send(ADMIN, StoreAction::ExportState { index: 0 }));
+    let mut chunks = vec![first.clone()];
+    for index in 1..first.total {
+        let res = store.send(ADMIN, StoreAction::ExportState { index });
+        chunks.push(exported_chunk(&res));
+    }
+    chunks
+}
+
+fn import(store: &Program<'_>, chunk: StateChunk) -> RunResult {
+    store.send(ADMIN, StoreAction::ImportState { chunk })
+}
+
+// what the first store program replies to `state()`, split into chunks by hand
+fn baseline_chunks(transactions: BTreeMap<ActorId, (u64, u32)>) -> Vec<StateChunk> {
+    let state = v1::AttributeStore {
+        admin: ActorId::from(ADMIN),
+        ft_contract_id: ActorId::from(FT_CONTRACT),
+        attributes: BTreeMap::from([(SKIN, (metadata(), PRICE)), (HAT, (metadata(), PRICE))]),
+        owners: BTreeMap::from([(ActorId::from(TAMAGOTCHI), BTreeSet::from([SKIN]))]),
+        transaction_id: 7,
+        transactions,
+    };
+    let data = state.encode();
+    let sum = checksum(&data);
+    let total = data.chunks(64).len() as u32;
+    data.chunks(64)
+        .enumerate()
+        .map(|(index, data)| StateChunk {
+            version: v1::STATE_VERSION,
+            index: index as u32,
+            total,
+            checksum: sum,
+            data: data.to_vec(),
+        })
+        .collect()
+}
+
+#[test]
+fn state_round_trip() {
+    let sys = System::new();
+    let (old_store, _) = init_store(&sys);
+    fill_catalog(&old_store);
+
+    // the state can only be taken from a frozen store
+    let res = old_store.send(ADMIN, StoreAction::ExportState { index: 0 });
+    assert!(res.main_failed());
+    freeze(&old_store);
+    let chunks = export(&old_store);
+    assert!(chunks.len() > 1);
+
+    let new_store = empty_store(&sys);
+    // nothing can be imported into a running store
+    assert!(import(&new_store, chunks[0].clone()).main_failed());
+    freeze(&new_store);
+
+    let total = chunks.len() as u32;
+    for chunk in chunks {
+        let index = chunk.index;
+        let res = import(&new_store, chunk);
+        let complete = index + 1 == total;
+        assert!(res.contains(&(
+            ADMIN,
+            StoreEvent::StateImported { index, complete }.encode()
+        )));
+    }
+
+    let new_state = state(&new_store);
+    assert_eq!(new_state.attributes.len(), ATTRIBUTES as usize + 2);
+    // the new store stays frozen until the owner checks it
+    assert!(new_state.frozen);
+    assert_eq!(new_state.encode(), state(&old_store).encode());
+}
+
+#[test]
+fn checksum_mismatch_is_rejected() {
+    let sys = System::new();
+    let (old_store, _) = init_store(&sys);
+    fill_catalog(&old_store);
+    freeze(&old_store);
+    let mut chunks = export(&old_store);
+
+    let new_store = empty_store(&sys);
+    freeze(&new_store);
+    let mut corrupted = chunks.pop().expect("No chunks exported");
+    corrupted.data[0] ^= 1;
+    for chunk in chunks {
+        assert!(!import(&new_store, chunk).main_failed());
+    }
+    assert!(import(&new_store, corrupted).main_failed());
+    assert!(state(&new_store).attributes.is_empty());
+}
+
+#[test]
+fn chunks_must_be_imported_in_order() {
+    let sys = System::new();
+    let (old_store, _) = init_store(&sys);
+    fill_catalog(&old_store);
+    freeze(&old_store);
+    let chunks = export(&old_store);
+
+    let new_store = empty_store(&sys);
+    freeze(&new_store);
+    assert!(import(&new_store, chunks[1].clone()).main_failed());
+
+    assert!(!import(&new_store, chunks[0].clone()).main_failed());
+    // the first chunk restarts the import, the others can't be repeated
+    assert!(!import(&new_store, chunks[0].clone()).main_failed());
+    assert!(!import(&new_store, chunks[1].clone()).main_failed());
+    assert!(import(&new_store, chunks[1].clone()).main_failed());
+}
+
+#[test]
+fn baseline_state_is_imported() {
+    let sys = System::new();
+    let balances = mock_ft(&sys, FT_CONTRACT);
+    let store = empty_store(&sys);
+    freeze(&store);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+
+    // pending transactions of the old store can't be finished by the new one
+    let pending = baseline_chunks(BTreeMap::from([(tamagotchi, (6, HAT))]));
+    for chunk in pending {
+        let complete = chunk.index + 1 == chunk.total;
+        assert_eq!(import(&store, chunk).main_failed(), complete);
+    }
+
+    let chunks = baseline_chunks(BTreeMap::new());
+    assert!(chunks.len() > 1);
+    for chunk in chunks {
+        assert!(!import(&store, chunk).main_failed());
+    }
+    let state = state(&store);
+    assert!(state.frozen);
+    assert_eq!(state.attributes.len(), 2);
+    assert_eq!(state.attributes[&HAT].1, PRICE);
+    assert_eq!(state.owners[&tamagotchi], BTreeSet::from([SKIN]));
+    assert_eq!(state.transaction_id, 7);
+    assert!(state.tx_timeout > 0);
+
+    // the imported store sells like the old one
+    admin_send(&store, StoreAction::Unfreeze);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).main_failed());
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+}
+
+#[test]
+fn frozen_store_empties_its_treasury() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+    admin_send(
+        &store,
+        StoreAction::SetRevenueSplit {
+            attribute_id: SKIN,
+            beneficiaries: vec![(ActorId::from(BENEFICIARY), 10)],
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    freeze(&store);
+    // the treasury and the payouts are still held
+    assert!(store
+        .send(ADMIN, StoreAction::ExportState { index: 0 })
+        .main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            token: ft_contract,
+            to: ActorId::from(ADMIN),
+            amount: PRICE - PRICE / 10,
+        },
+    );
+    assert!(res.contains(&(
+        ADMIN,
+        StoreEvent::Withdrawn {
+            amount: PRICE - PRICE / 10,
+            success: true,
+        }
+        .encode()
+    )));
+    // the beneficiary doesn't have to claim its share itself
+    let res = store.send(
+        ADMIN,
+        StoreAction::PayOut {
+            beneficiary: ActorId::from(BENEFICIARY),
+            token: ft_contract,
+        },
+    );
+    assert!(res.contains(&(
+        ADMIN,
+        StoreEvent::RevenueClaimed {
+            amount: PRICE / 10,
+            success: true,
+        }
+        .encode()
+    )));
+    assert_eq!(balance(&balances, BENEFICIARY), PRICE / 10);
+    assert_eq!(balance(&balances, ADMIN), PRICE - PRICE / 10);
+
+    assert!(!export(&store).is_empty());
+}
@@ -0,0 +1,63 @@
+use gstd::{prelude::*, ActorId};
+use gtest::{Program, System};
+use store_io::{AttributeId, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+const BOX: AttributeId = 3;
+
+fn open_box(store: &Program<'_>, balances: &Balances) -> AttributeId {
+    let spent = BALANCE - balance(balances, TAMAGOTCHI);
+    let owned = state(store)
+        .owners
+        .get(&ActorId::from(TAMAGOTCHI))
+        .cloned()
+        .unwrap_or_default();
+
+    let res = store.send(TAMAGOTCHI, StoreAction::BuyMysteryBox { box_id: BOX });
+    let state = state(store);
+    let drawn = *state.owners[&ActorId::from(TAMAGOTCHI)]
+        .difference(&owned)
+        .next()
+        .expect("Nothing is drawn from the box");
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::MysteryBoxOpened {
+            attribute_id: drawn
+        }
+        .encode()
+    )));
+    assert_eq!(balance(balances, TAMAGOTCHI), BALANCE - spent - PRICE);
+    drawn
+}
+
+#[test]
+fn box_gives_an_attribute_from_its_pool() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    create_attribute(&store, BOX);
+    // only mystery boxes can be bought as such
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::BuyMysteryBox { box_id: BOX })
+        .main_failed());
+    admin_send(
+        &store,
+        StoreAction::SetMysteryBox {
+            box_id: BOX,
+            pool: vec![(SKIN, 1), (HAT, 3)],
+        },
+    );
+
+    // an owned attribute isn't drawn again
+    let first = open_box(&store, &balances);
+    let second = open_box(&store, &balances);
+    assert_ne!(first, second);
+    assert!([SKIN, HAT].contains(&first) && [SKIN, HAT].contains(&second));
+    assert!(!state(&store).owners[&ActorId::from(TAMAGOTCHI)].contains(&BOX));
+
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::BuyMysteryBox { box_id: BOX })
+        .main_failed());
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - 2 * PRICE);
+}
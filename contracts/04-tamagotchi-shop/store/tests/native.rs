@@ -0,0 +1,62 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Currency, StoreAction};
+
+mod common;
+use common::*;
+
+const NATIVE_PRICE: u128 = 10_000;
+
+#[test]
+fn native_change_is_returned() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+
+    admin_send(
+        &store,
+        StoreAction::SetNativePrice {
+            attribute_id: SKIN,
+            price: Some(NATIVE_PRICE),
+        },
+    );
+
+    sys.mint_to(TAMAGOTCHI, 2 * NATIVE_PRICE);
+    let store_balance = sys.balance_of(store.id());
+    let res = store.send_with_value(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute { attribute_id: SKIN },
+        NATIVE_PRICE + NATIVE_PRICE / 2,
+    );
+    assert!(res.contains(&sold(TAMAGOTCHI, true)));
+    // only the price stays with the store
+    assert_eq!(sys.balance_of(store.id()), store_balance + NATIVE_PRICE);
+
+    let state = state(&store);
+    assert_eq!(state.native_treasury, NATIVE_PRICE);
+    assert_eq!(
+        state.purchases[&(ActorId::from(TAMAGOTCHI), SKIN)].currency,
+        Currency::Native
+    );
+}
+
+#[test]
+fn not_enough_value_is_rejected() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+
+    admin_send(
+        &store,
+        StoreAction::SetNativePrice {
+            attribute_id: SKIN,
+            price: Some(NATIVE_PRICE),
+        },
+    );
+    sys.mint_to(TAMAGOTCHI, 2 * NATIVE_PRICE);
+    let res = store.send_with_value(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute { attribute_id: SKIN },
+        NATIVE_PRICE - 1,
+    );
+    assert!(res.main_failed());
+    assert!(state(&store).purchases.is_empty());
+}
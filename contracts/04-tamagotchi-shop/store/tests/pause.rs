@@ -0,0 +1,32 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{StoreAction, StoreError, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn pause_blocks_purchases_only() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+
+    admin_send(&store, StoreAction::Pause);
+    let res = buy(&store, TAMAGOTCHI, SKIN);
+    assert!(res.contains(&(TAMAGOTCHI, StoreEvent::Error(StoreError::Paused).encode())));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE);
+
+    // reads and the catalog keep working
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GetAttributes {
+            tamagotchi_id: ActorId::from(TAMAGOTCHI),
+        },
+    );
+    assert!(!res.main_failed());
+    create_attribute(&store, 3);
+
+    // only operators can unpause
+    assert!(store.send(TAMAGOTCHI, StoreAction::Unpause).main_failed());
+    admin_send(&store, StoreAction::Unpause);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+}
@@ -0,0 +1,57 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Discount, Presale, StoreAction, StoreError, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn presale_is_for_whitelisted_tamagotchis() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let whitelist_only = |tamagotchi| {
+        (
+            tamagotchi,
+            StoreEvent::Error(StoreError::WhitelistOnly).encode(),
+        )
+    };
+
+    admin_send(
+        &store,
+        StoreAction::SetPresale {
+            attribute_id: SKIN,
+            presale: Some(Presale {
+                ends_at: sys.block_height() + 50,
+                discount: Some(Discount::Percent(10)),
+            }),
+        },
+    );
+    admin_send(
+        &store,
+        StoreAction::AddToWhitelist {
+            attribute_id: SKIN,
+            tamagotchis: vec![ActorId::from(TAMAGOTCHI)],
+        },
+    );
+
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).contains(&whitelist_only(OTHER_TAMAGOTCHI)));
+    // a whitelisted buyer can't gift it to somebody who isn't whitelisted either
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::GiftAttribute {
+            attribute_id: SKIN,
+            recipient: ActorId::from(OTHER_TAMAGOTCHI),
+        },
+    );
+    assert!(res.contains(&whitelist_only(TAMAGOTCHI)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE);
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE * 90 / 100);
+
+    // everybody pays the full price after the presale
+    sys.spend_blocks(50);
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).contains(&sold(OTHER_TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE - PRICE);
+}
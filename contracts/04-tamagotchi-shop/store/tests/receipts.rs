@@ -0,0 +1,39 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::Currency;
+
+mod common;
+use common::*;
+
+#[test]
+fn every_purchase_attempt_is_receipted() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+    let ft_contract = Currency::FungibleToken(ActorId::from(FT_CONTRACT));
+
+    assert!(buy(&store, POOR_TAMAGOTCHI, SKIN).contains(&sold(POOR_TAMAGOTCHI, false)));
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+
+    let state = state(&store);
+    // failed attempts don't count as sales
+    assert_eq!(state.sale_count, 2);
+
+    let receipts = &state.receipts[&ActorId::from(POOR_TAMAGOTCHI)];
+    assert_eq!(receipts.len(), 1);
+    assert!(!receipts[0].success);
+    assert_eq!(receipts[0].attribute_id, SKIN);
+
+    let receipts = &state.receipts[&ActorId::from(TAMAGOTCHI)];
+    assert_eq!(
+        receipts
+            .iter()
+            .map(|receipt| (receipt.attribute_id, receipt.price, receipt.success))
+            .collect::<Vec<_>>(),
+        [(SKIN, PRICE, true), (HAT, PRICE, true)]
+    );
+    assert!(receipts.iter().all(
+        |receipt| receipt.buyer == ActorId::from(TAMAGOTCHI) && receipt.currency == ft_contract
+    ));
+    assert_ne!(receipts[0].transaction_id, receipts[1].transaction_id);
+}
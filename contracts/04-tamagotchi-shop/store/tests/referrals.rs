@@ -0,0 +1,90 @@
+use gstd::{prelude::*, ActorId};
+use gtest::{Program, RunResult, System};
+use store_io::{AttributeId, StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+// a tenth of the price
+const REWARD: u128 = PRICE / 10;
+
+fn buy_referred(store: &Program<'_>, tamagotchi: u64, attribute_id: AttributeId) -> RunResult {
+    store.send(
+        tamagotchi,
+        StoreAction::BuyAttributeWith {
+            attribute_id,
+            pay_with: None,
+            coupon: None,
+            points: 0,
+            referrer: Some(ActorId::from(OTHER_TAMAGOTCHI)),
+        },
+    )
+}
+
+fn init_referrals(sys: &System) -> (Program<'_>, Balances) {
+    let (store, balances) = init_store(sys);
+    admin_send(&store, StoreAction::SetReferralReward { percent: 10 });
+    (store, balances)
+}
+
+#[test]
+fn first_purchase_rewards_the_referrer() {
+    let sys = System::new();
+    let (store, balances) = init_referrals(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+    let referrer = ActorId::from(OTHER_TAMAGOTCHI);
+
+    assert!(buy_referred(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    // only the first purchase is rewarded
+    assert!(buy_referred(&store, TAMAGOTCHI, HAT).contains(&sold(TAMAGOTCHI, true)));
+
+    let state = state(&store);
+    assert_eq!(state.payouts[&(referrer, ft_contract)], REWARD);
+    assert_eq!(state.treasury[&ft_contract], 2 * PRICE - REWARD);
+    assert_eq!(state.referred_by[&ActorId::from(TAMAGOTCHI)], referrer);
+    assert_eq!(state.referrals[&referrer], 1);
+
+    let res = store.send(
+        OTHER_TAMAGOTCHI,
+        StoreAction::ClaimRevenue { token: ft_contract },
+    );
+    assert!(res.contains(&(
+        OTHER_TAMAGOTCHI,
+        StoreEvent::RevenueClaimed {
+            amount: REWARD,
+            success: true,
+        }
+        .encode()
+    )));
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE + REWARD);
+}
+
+#[test]
+fn sell_back_claws_the_reward_back() {
+    let sys = System::new();
+    let (store, balances) = init_referrals(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+    admin_send(
+        &store,
+        StoreAction::SetRefundPolicy {
+            percent: 50,
+            window: 10,
+        },
+    );
+
+    assert!(buy_referred(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: SKIN });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::AttributeSoldBack { success: true }.encode()
+    )));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE / 2);
+
+    let state = state(&store);
+    assert!(state.payouts.is_empty());
+    assert_eq!(state.treasury[&ft_contract], PRICE / 2);
+    assert_eq!(
+        state.referral_rewards[&(ActorId::from(OTHER_TAMAGOTCHI), ft_contract)],
+        0
+    );
+}
@@ -0,0 +1,92 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn revenue_split_is_paid_out() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+    let beneficiary = ActorId::from(BENEFICIARY);
+
+    admin_send(
+        &store,
+        StoreAction::SetRevenueSplit {
+            attribute_id: SKIN,
+            beneficiaries: vec![(beneficiary, 30)],
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    let state_after_sale = state(&store);
+    assert_eq!(
+        state_after_sale.payouts[&(beneficiary, ft_contract)],
+        PRICE * 30 / 100
+    );
+    assert_eq!(state_after_sale.treasury[&ft_contract], PRICE * 70 / 100);
+    assert_eq!(state_after_sale.total_revenue[&ft_contract], PRICE);
+
+    let res = store.send(
+        BENEFICIARY,
+        StoreAction::ClaimRevenue { token: ft_contract },
+    );
+    assert!(res.contains(&(
+        BENEFICIARY,
+        StoreEvent::RevenueClaimed {
+            amount: PRICE * 30 / 100,
+            success: true,
+        }
+        .encode()
+    )));
+    assert_eq!(balance(&balances, BENEFICIARY), PRICE * 30 / 100);
+    assert!(state(&store).payouts.is_empty());
+
+    // nothing is left to claim
+    let res = store.send(
+        BENEFICIARY,
+        StoreAction::ClaimRevenue { token: ft_contract },
+    );
+    assert!(res.main_failed());
+}
+
+#[test]
+fn treasury_is_withdrawn() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    // more than the store has earned can't be taken out
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            token: ft_contract,
+            to: ActorId::from(BENEFICIARY),
+            amount: PRICE + 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = admin_send(
+        &store,
+        StoreAction::Withdraw {
+            token: ft_contract,
+            to: ActorId::from(BENEFICIARY),
+            amount: PRICE,
+        },
+    );
+    assert!(res.contains(&(
+        ADMIN,
+        StoreEvent::Withdrawn {
+            amount: PRICE,
+            success: true,
+        }
+        .encode()
+    )));
+    assert_eq!(balance(&balances, BENEFICIARY), PRICE);
+    assert_eq!(state(&store).treasury[&ft_contract], 0);
+}
@@ -0,0 +1,62 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{Role, StoreAction};
+
+mod common;
+use common::*;
+
+const CATALOG_MANAGER: u64 = 40;
+
+#[test]
+fn roles_are_granted_and_revoked() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+    let create = |from: u64, attribute_id| {
+        store.send(
+            from,
+            StoreAction::CreateAttribute {
+                attribute_id,
+                attr_metadata: metadata(),
+                price: PRICE,
+            },
+        )
+    };
+
+    assert!(create(CATALOG_MANAGER, 3).main_failed());
+    admin_send(
+        &store,
+        StoreAction::GrantRole {
+            account: ActorId::from(CATALOG_MANAGER),
+            role: Role::CatalogManager,
+        },
+    );
+    assert!(!create(CATALOG_MANAGER, 3).main_failed());
+
+    // the role doesn't give anything else
+    let res = store.send(
+        CATALOG_MANAGER,
+        StoreAction::SetRefundPolicy {
+            percent: 100,
+            window: 10,
+        },
+    );
+    assert!(res.main_failed());
+    let res = store.send(
+        CATALOG_MANAGER,
+        StoreAction::GrantRole {
+            account: ActorId::from(TAMAGOTCHI),
+            role: Role::CatalogManager,
+        },
+    );
+    assert!(res.main_failed());
+
+    admin_send(
+        &store,
+        StoreAction::RevokeRole {
+            account: ActorId::from(CATALOG_MANAGER),
+            role: Role::CatalogManager,
+        },
+    );
+    assert!(create(CATALOG_MANAGER, 4).main_failed());
+    assert!(state(&store).roles.is_empty());
+}
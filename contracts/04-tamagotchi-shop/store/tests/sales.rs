@@ -0,0 +1,56 @@
+use gstd::prelude::*;
+use gtest::System;
+use store_io::{Discount, Sale, StoreAction};
+
+mod common;
+use common::*;
+
+#[test]
+fn attribute_is_sold_within_its_window() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let now = sys.block_height();
+
+    admin_send(
+        &store,
+        StoreAction::SetAvailability {
+            attribute_id: SKIN,
+            available_from: Some(now + 10),
+            available_until: Some(now + 20),
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).main_failed());
+
+    sys.spend_blocks(10);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    sys.spend_blocks(10);
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).main_failed());
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE);
+}
+
+#[test]
+fn scheduled_sale_discounts_the_price() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let now = sys.block_height();
+
+    admin_send(
+        &store,
+        StoreAction::ScheduleSale {
+            attribute_id: SKIN,
+            sale: Sale {
+                starts_at: now,
+                ends_at: now + 100,
+                discount: Discount::Percent(20),
+            },
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE * 80 / 100);
+
+    // the sale is over once it's cleared
+    admin_send(&store, StoreAction::ClearSales { attribute_id: SKIN });
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).contains(&sold(OTHER_TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, OTHER_TAMAGOTCHI), BALANCE - PRICE);
+}
@@ -0,0 +1,62 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::{StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn sell_back_refunds_the_price() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let ft_contract = ActorId::from(FT_CONTRACT);
+
+    admin_send(
+        &store,
+        StoreAction::SetRefundPolicy {
+            percent: 50,
+            window: 10,
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: SKIN });
+    assert!(res.contains(&(
+        TAMAGOTCHI,
+        StoreEvent::AttributeSoldBack { success: true }.encode()
+    )));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE / 2);
+
+    let state = state(&store);
+    assert!(state.purchases.is_empty());
+    assert_eq!(state.treasury[&ft_contract], PRICE / 2);
+    assert_eq!(state.revenue[&(SKIN, ft_contract)], PRICE / 2);
+    assert_eq!(state.total_revenue[&ft_contract], PRICE / 2);
+
+    // the attribute is gone, so it can't be refunded twice
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: SKIN });
+    assert!(res.main_failed());
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE / 2);
+}
+
+#[test]
+fn refund_window_expires() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+
+    admin_send(
+        &store,
+        StoreAction::SetRefundPolicy {
+            percent: 100,
+            window: 5,
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    sys.spend_blocks(5);
+    let res = store.send(TAMAGOTCHI, StoreAction::SellBack { attribute_id: SKIN });
+    assert!(res.main_failed());
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+    assert!(state(&store).owners[&ActorId::from(TAMAGOTCHI)].contains(&SKIN));
+}
@@ -0,0 +1,108 @@
+use gstd::{prelude::*, ActorId};
+use gtest::{System, WasmProgram};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use store_io::{StoreAction, TransferReason};
+
+mod common;
+use common::*;
+
+// takes the tokens of the tamagotchis, but never replies to transfers out of the store
+#[derive(Debug)]
+struct PayInOnlyFt;
+
+impl WasmProgram for PayInOnlyFt {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        let FTokenAction::Message {
+            payload: LogicAction::Transfer { sender, .. },
+            ..
+        } = FTokenAction::decode(&mut &payload[..]).map_err(|_| "Unable to decode the action")?
+        else {
+            return Err("Only transfers are supported");
+        };
+        let paid_in = [TAMAGOTCHI, OTHER_TAMAGOTCHI]
+            .into_iter()
+            .any(|tamagotchi| sender == ActorId::from(tamagotchi));
+        Ok(paid_in.then(|| FTokenEvent::Ok.encode()))
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn only_flagged_transactions_are_reconciled() {
+    let sys = System::new();
+    let store = init_store_with(&sys, SilentFt);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+
+    assert!(!buy(&store, TAMAGOTCHI, SKIN).main_failed());
+    assert!(state(&store).transactions.contains_key(&tamagotchi));
+    // the purchase is still awaited, it didn't run out of gas
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::ReconcileTx {
+            tamagotchi_id: tamagotchi,
+        },
+    );
+    assert!(res.main_failed());
+    assert!(state(&store).reconciliations.is_empty());
+}
+
+#[test]
+fn outgoing_transfer_is_recorded_until_settled() {
+    let sys = System::new();
+    let store = init_store_with(&sys, PayInOnlyFt);
+    let beneficiary = ActorId::from(BENEFICIARY);
+    admin_send(
+        &store,
+        StoreAction::SetRevenueSplit {
+            attribute_id: SKIN,
+            beneficiaries: vec![(beneficiary, 10)],
+        },
+    );
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+
+    let res = store.send(
+        BENEFICIARY,
+        StoreAction::ClaimRevenue {
+            token: ActorId::from(FT_CONTRACT),
+        },
+    );
+    assert!(!res.main_failed());
+    let state = state(&store);
+    assert!(state.payouts.is_empty());
+    let (&transaction_id, transfer) = state
+        .transfers
+        .iter()
+        .next()
+        .expect("Transfer isn't recorded");
+    assert_eq!(transfer.to, beneficiary);
+    assert_eq!(transfer.amount, PRICE / 10);
+    assert!(matches!(transfer.reason, TransferReason::Payout));
+
+    let reconcile = |from| store.send(from, StoreAction::ReconcileTransfer { transaction_id });
+    // only the recipient or an operator can reconcile it, and not while it's still awaited
+    assert!(reconcile(TAMAGOTCHI).main_failed());
+    assert!(reconcile(BENEFICIARY).main_failed());
+    assert!(store
+        .send(
+            BENEFICIARY,
+            StoreAction::ReconcileTransfer {
+                transaction_id: transaction_id + 1,
+            },
+        )
+        .main_failed());
+}
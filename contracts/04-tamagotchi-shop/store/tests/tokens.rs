@@ -0,0 +1,68 @@
+use gstd::{prelude::*, ActorId};
+use gtest::System;
+use store_io::StoreAction;
+
+mod common;
+use common::*;
+
+const TOKEN_PRICE: u128 = 300;
+
+#[test]
+fn attribute_is_paid_with_accepted_token() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let new_balances = mock_ft(&sys, NEW_FT_CONTRACT);
+    let new_ft_contract = ActorId::from(NEW_FT_CONTRACT);
+    let buy_with_new_token = |attribute_id| {
+        store.send(
+            TAMAGOTCHI,
+            StoreAction::BuyAttributeWith {
+                attribute_id,
+                pay_with: Some(new_ft_contract),
+                coupon: None,
+                points: 0,
+                referrer: None,
+            },
+        )
+    };
+
+    // the token isn't accepted yet
+    assert!(buy_with_new_token(SKIN).main_failed());
+    admin_send(
+        &store,
+        StoreAction::AcceptToken {
+            token: new_ft_contract,
+        },
+    );
+    admin_send(
+        &store,
+        StoreAction::SetTokenPrice {
+            attribute_id: SKIN,
+            token: new_ft_contract,
+            price: Some(TOKEN_PRICE),
+        },
+    );
+    // only attributes priced in the token can be bought with it
+    assert!(buy_with_new_token(HAT).main_failed());
+
+    assert!(buy_with_new_token(SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&new_balances, TAMAGOTCHI), BALANCE - TOKEN_PRICE);
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE);
+    let state = state(&store);
+    assert_eq!(state.treasury[&new_ft_contract], TOKEN_PRICE);
+    assert!(!state.treasury.contains_key(&ActorId::from(FT_CONTRACT)));
+}
+
+#[test]
+fn baseline_buy_encoding_is_accepted() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+
+    // `BuyAttribute { attribute_id }` as the tamagotchis of the previous lessons encode it
+    let mut payload = vec![1];
+    payload.extend(SKIN.to_le_bytes());
+    assert!(store
+        .send_bytes(TAMAGOTCHI, payload)
+        .contains(&sold(TAMAGOTCHI, true)));
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+}
@@ -0,0 +1,87 @@
+use gstd::{collections::BTreeSet, prelude::*, ActorId};
+use gtest::System;
+use store_io::{StoreAction, StoreEvent};
+
+mod common;
+use common::*;
+
+#[test]
+fn trade_swaps_attributes() {
+    let sys = System::new();
+    let (store, _) = init_store(&sys);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+    let other_tamagotchi = ActorId::from(OTHER_TAMAGOTCHI);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, OTHER_TAMAGOTCHI, HAT).contains(&sold(OTHER_TAMAGOTCHI, true)));
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::OfferTrade {
+            give: SKIN,
+            want: HAT,
+            counterparty: other_tamagotchi,
+        },
+    );
+    let trade_id = *state(&store)
+        .trades
+        .keys()
+        .next()
+        .expect("Trade isn't offered");
+    assert!(res.contains(&(TAMAGOTCHI, StoreEvent::TradeOffered { trade_id }.encode())));
+    // only the counterparty can accept it
+    assert!(store
+        .send(TAMAGOTCHI, StoreAction::AcceptTrade { trade_id })
+        .main_failed());
+
+    let res = store.send(OTHER_TAMAGOTCHI, StoreAction::AcceptTrade { trade_id });
+    assert!(res.contains(&(
+        OTHER_TAMAGOTCHI,
+        StoreEvent::TradeAccepted { trade_id }.encode()
+    )));
+    let state = state(&store);
+    assert!(state.trades.is_empty());
+    assert_eq!(state.owners[&tamagotchi], BTreeSet::from([HAT]));
+    assert_eq!(state.owners[&other_tamagotchi], BTreeSet::from([SKIN]));
+}
+
+#[test]
+fn escrowed_attribute_cant_be_received_again() {
+    let sys = System::new();
+    let (store, balances) = init_store(&sys);
+    let tamagotchi = ActorId::from(TAMAGOTCHI);
+    assert!(buy(&store, TAMAGOTCHI, SKIN).contains(&sold(TAMAGOTCHI, true)));
+    assert!(buy(&store, OTHER_TAMAGOTCHI, SKIN).contains(&sold(OTHER_TAMAGOTCHI, true)));
+
+    assert!(!store
+        .send(
+            TAMAGOTCHI,
+            StoreAction::OfferTrade {
+                give: SKIN,
+                want: HAT,
+                counterparty: ActorId::from(OTHER_TAMAGOTCHI),
+            },
+        )
+        .main_failed());
+    let trade_id = *state(&store)
+        .trades
+        .keys()
+        .next()
+        .expect("Trade isn't offered");
+
+    // the offered skin is still the tamagotchi's, so it can't be bought or transferred to it
+    assert!(buy(&store, TAMAGOTCHI, SKIN).main_failed());
+    assert_eq!(balance(&balances, TAMAGOTCHI), BALANCE - PRICE);
+    assert!(store
+        .send(
+            OTHER_TAMAGOTCHI,
+            StoreAction::TransferAttribute {
+                attribute_id: SKIN,
+                to: tamagotchi,
+            },
+        )
+        .main_failed());
+
+    let res = store.send(TAMAGOTCHI, StoreAction::CancelTrade { trade_id });
+    assert!(res.contains(&(TAMAGOTCHI, StoreEvent::TradeCancelled { trade_id }.encode())));
+    assert!(state(&store).owners[&tamagotchi].contains(&SKIN));
+}